@@ -28,6 +28,10 @@ pub struct Runnable;
 #[doc = docify::embed_run!("examples/samples.rs", test_with_custom_name)]
 pub struct RunnableTest;
 
+/// This example is compiled but not run in rust docs
+#[doc = docify::embed_no_run!("examples/samples.rs", runnable)]
+pub struct NoRun;
+
 /// This example has comments
 #[doc = docify::embed!("examples/samples.rs", some_complex_example)]
 pub struct LineComments;
@@ -71,7 +75,7 @@ pub struct LiamExample9;
 pub struct ExportContentImpl;
 
 /// This demonstrates using `#[docify::export_content]` on a trait
-#[doc = docify::embed_run!("examples/samples.rs", SomeOtherTrait)]
+#[doc = docify::embed_run!("examples/samples.rs", SomeOtherTrait, no_check)]
 pub struct ExportContentTrait;
 
 /// This demonstrates using `#[docify::export_content]` on an fn
@@ -92,6 +96,18 @@ pub struct ExportContentConst;
 #[doc = docify::embed!("examples/samples.rs", inner_mod2)]
 pub struct NestedExports;
 
+/// This embeds a specific range of lines rather than an exported item
+#[doc = docify::embed!("examples/samples.rs", 11..=14)]
+pub struct LineRange;
+
+/// This example says where it came from
+#[doc = docify::embed!("examples/samples.rs", some_example, show_source)]
+pub struct ShowSource;
+
+/// This embeds a single method from an impl block by its type and method name
+#[doc = docify::embed!("examples/samples.rs", MyCoolStruct::bar)]
+pub struct ImplMethod;
+
 /// This will compile all markdown files in the `markdown_source` directory to `markdown_bin`
 /// when `cargo doc` is run, handling any doc embed calls as it goes
 #[cfg(doc)]
@@ -3,6 +3,7 @@
 //! ```
 
 #![allow(unused)]
+#![allow(clippy::eq_op, clippy::redundant_static_lifetimes)]
 
 use proc_utils::*;
 
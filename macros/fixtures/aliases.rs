@@ -0,0 +1,9 @@
+#[docify::export(setup, setup_example)]
+fn initialize() {
+    println!("ready");
+}
+
+#[docify::export(setup)]
+fn other_setup() {
+    println!("also ready");
+}
@@ -0,0 +1,16 @@
+#[derive(Debug)]
+#[docify::export]
+#[repr(u8)]
+enum Level {
+    Low = 1,
+    High = 2,
+}
+
+#[inline]
+#[must_use]
+#[docify::export(doubled)]
+#[allow(clippy::identity_op)]
+#[cfg_attr(test, allow(dead_code))]
+fn double(x: u32) -> u32 {
+    x * 2
+}
@@ -0,0 +1,16 @@
+#[docify::export]
+#[tokio::main]
+async fn main() {
+    run().await;
+}
+
+#[docify::export]
+#[derive(Debug, serde::Serialize)]
+#[serde(
+    rename_all = "camelCase"
+)]
+struct Config {
+    #[serde(rename = "id")] user_id: u32,
+    // #[serde(skip)] is mentioned in a comment
+    name: String,
+}
@@ -0,0 +1,6 @@
+#[docify::export]
+const README: &str = "
+```rust
+let x = 1;
+```
+";
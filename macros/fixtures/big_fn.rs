@@ -0,0 +1,11 @@
+#[docify::export]
+fn big_fn() {
+    let mut total = 0;
+    for i in 0..10 {
+        total += i;
+    }
+    if total > 40 {
+        println!("big total: {}", total);
+    }
+    println!("done");
+}
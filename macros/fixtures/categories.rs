@@ -0,0 +1,13 @@
+#[docify::export(category = "networking")]
+fn connect() {}
+
+#[docify::export]
+fn unrelated() {}
+
+#[docify::export(fetch_example, category = "networking")]
+fn fetch() {}
+
+mod storage {
+    #[docify::export(category = "storage")]
+    fn save() {}
+}
@@ -0,0 +1,17 @@
+#[docify::export(example)]
+#[cfg(feature = "docify-test-enabled")]
+fn enabled() {}
+
+#[docify::export(example)]
+#[cfg(feature = "docify-test-disabled")]
+fn disabled() {}
+
+#[docify::export(example)]
+#[cfg(all(not(feature = "docify-test-disabled"), unix))]
+fn unknown() {}
+
+#[cfg(any(feature = "docify-test-disabled"))]
+mod inactive {
+    #[docify::export(example)]
+    fn nested() {}
+}
@@ -0,0 +1,6 @@
+#[cfg_attr(feature = "docify-test-enabled", docify::export(documented))]
+pub fn enabled_in_docs() {}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "docify-test-disabled", docify::export(documented))]
+pub struct DisabledInDocs;
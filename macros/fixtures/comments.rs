@@ -0,0 +1,13 @@
+#[docify::export]
+fn commented() -> i32 {
+    // explanatory comment
+    let x = 5; // trailing comment
+    /* block comment */
+    x + 1 /* inline */
+} // end of commented
+
+fn other() {
+    #[docify::export]
+    let y = 7; /* trailing block comment */
+    // this comment is not part of `y`
+}
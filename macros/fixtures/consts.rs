@@ -0,0 +1,14 @@
+#[docify::export]
+pub const MAX_SIZE: usize = 1 << 20;
+
+#[docify::export]
+const TIMEOUT: u64 = (60 * 5 + 30) * 1_000; // in milliseconds
+
+#[docify::export]
+static MASK: u8 = (0xF0u8 >> 4) as u8 ^ 0b0101;
+
+#[docify::export]
+const NAME: &str = "docify";
+
+#[docify::export]
+fn not_a_const() {}
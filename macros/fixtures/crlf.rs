@@ -0,0 +1,6 @@
+#[docify::export]
+fn crlf_example() {
+    let x = 1;
+
+    println!("{}", x);
+}
@@ -0,0 +1,6 @@
+#[derive(Default)]
+#[docify::export(setup, desc = "Sets up the *client*, see `connect`")]
+pub struct Client;
+
+#[docify::export(setup)]
+pub fn connect(client: &Client) {}
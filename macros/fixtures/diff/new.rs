@@ -0,0 +1,8 @@
+#[docify::export]
+fn setup() {
+    let client = Client::builder().timeout(30).build();
+    client.connect("localhost");
+}
+
+#[docify::export]
+fn added() {}
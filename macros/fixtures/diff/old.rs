@@ -0,0 +1,8 @@
+#[docify::export]
+fn setup() {
+    let client = Client::new();
+    client.connect("localhost");
+}
+
+#[docify::export]
+fn removed() {}
@@ -0,0 +1,11 @@
+#[docify::export]
+#[doc(hidden)]
+pub fn internal_helper() -> u32 {
+    42
+}
+
+#[docify::export]
+pub struct Handle {
+    pub id: u32,
+    #[doc( hidden )] pub generation: u32,
+}
@@ -0,0 +1,11 @@
+#[docify::export]
+#[inline]
+/// Adds one.
+fn documented(value: u32) -> u32 {
+    /// A helper.
+    #[doc = "More docs."]
+    struct Helper;
+    //// Not a doc comment.
+    // A regular comment.
+    value + 1
+}
@@ -0,0 +1,10 @@
+#[docify::export(Duplicate)]
+struct StructOne;
+
+mod inner {
+    #[docify::export(Duplicate)]
+    struct StructTwo;
+}
+
+#[docify::export]
+struct Single;
@@ -0,0 +1,9 @@
+fn setup() {
+    let unrelated = 1;
+    docify::export_stmt!(create_client, {
+        let client = Client::new("localhost");
+        // connect before sending anything
+        client.connect();
+    });
+    client.send(unrelated);
+}
@@ -0,0 +1,11 @@
+extern "C" {
+    #[docify::export]
+    fn foo();
+
+    /// Returns the absolute value of `x`.
+    #[docify::export(c_abs)]
+    fn abs(x: i32) -> i32;
+
+    #[docify::export]
+    static errno: i32;
+}
@@ -0,0 +1,5 @@
+#[docify::export]
+fn unique() {}
+
+#[docify::export]
+fn other() {}
@@ -0,0 +1,13 @@
+mod outer {
+    #[docify::export]
+    fn aligned() {
+        let short       = 1;
+        let much_longer = 2;
+
+        //  +--------+
+        //  | boxes! |
+        //  +--------+
+        let total = short
+                  + much_longer;
+    }
+}
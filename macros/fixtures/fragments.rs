@@ -0,0 +1,4 @@
+pub trait Greeter {
+    #[docify::export]
+    fn greet(&self) -> String;
+}
@@ -0,0 +1,15 @@
+#[docify::export]
+pub fn merge_all<'a, 'b: 'a, K, V, I, const N: usize>(
+    maps: I,
+    extra: &'b [(K, V); N],
+) -> std::collections::BTreeMap<&'a K, &'a V>
+where
+    K: Ord + Clone + 'b,
+    V: std::fmt::Debug + ?Sized,
+    I: IntoIterator<Item = &'a std::collections::BTreeMap<K, V>>,
+    for<'c> &'c V: PartialEq<&'c V>,
+{
+    let mut merged = maps.into_iter().flatten().collect::<std::collections::BTreeMap<_, _>>();
+    merged.extend(extra.iter().map(|(k, v)| (k, v)));
+    merged
+}
@@ -0,0 +1,16 @@
+#[docify::export(counter_example)]
+struct Counter {
+    count: u32,
+}
+
+#[docify::export(counter_example)]
+impl Counter {
+    fn increment(&mut self) {
+        self.count += 1;
+    }
+}
+
+#[docify::export(counter_example)]
+fn new_counter() -> Counter {
+    Counter { count: 0 }
+}
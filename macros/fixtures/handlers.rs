@@ -0,0 +1,14 @@
+#[docify::export]
+fn handle_get() {}
+
+#[docify::export]
+fn route() {}
+
+#[docify::export(handle_post)]
+fn post() {}
+
+fn serve() {
+    docify::export_stmt!(handle_put, {
+        put();
+    });
+}
@@ -0,0 +1,15 @@
+#[docify::export]
+fn long_example() {
+    let client = 1;
+    // docify:hide-start
+    let retries = 3;
+    let timeout = 30;
+    // docify:hide-end
+    println!("{}", client);
+}
+
+#[docify::export]
+fn unterminated() {
+    // docify:hide-start
+    let hidden = true;
+}
@@ -0,0 +1,25 @@
+fn example() {
+    println!("shown");
+}
+
+/// Runs the example.
+#[docify::hide]
+fn main() {
+    example();
+}
+
+struct Shown;
+
+impl Shown {
+    #[docify::hide]
+    fn helper(&self) {}
+
+    fn visible(&self) {}
+}
+
+#[docify::hide]
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn it_works() {}
+}
@@ -0,0 +1,22 @@
+struct Foo;
+
+#[docify::export]
+impl Foo {
+    fn new() -> Self {
+        Foo
+    }
+}
+
+#[docify::export]
+impl std::fmt::Display for Foo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Foo")
+    }
+}
+
+#[docify::export(foo_ref_default)]
+impl Default for &Foo {
+    fn default() -> Self {
+        &Foo
+    }
+}
@@ -0,0 +1,36 @@
+struct MyType;
+
+impl MyType {
+    /// The largest supported value
+    const MAX: u32 = 100;
+
+    fn new() -> Self {
+        MyType
+    }
+
+    /// Does something
+    fn my_method(&self) -> u32 {
+        42
+    }
+}
+
+impl std::fmt::Display for MyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MyType")
+    }
+}
+
+impl<T> Wrapper<T> {
+    #[docify::export]
+    fn unwrap(self) -> T {
+        self.0
+    }
+}
+
+impl Iterator for MyType {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        None
+    }
+}
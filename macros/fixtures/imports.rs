@@ -0,0 +1,18 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Display as Show};
+use std::io::*;
+use std::sync::Arc;
+
+#[docify::export]
+fn count_words(text: &str) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[docify::export]
+fn describe<T: Show>(value: T) -> String {
+    format!("{}", value)
+}
@@ -0,0 +1,13 @@
+fn outer() -> u32 {
+    #[docify::export(inner)]
+    fn helper(x: u32) -> u32 {
+        x * 2
+    }
+
+    let closure = || {
+        #[docify::export]
+        fn deeply_nested() {}
+    };
+    closure();
+    helper(21)
+}
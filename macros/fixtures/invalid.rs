@@ -0,0 +1,12 @@
+macro_rules! make_struct {
+    ($name:ident) => {
+        pub struct $name;
+    };
+}
+
+make_struct!(Generated);
+
+#[docify::export]
+pub fn broken() {
+    let x = 1 + ;
+}
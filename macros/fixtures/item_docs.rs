@@ -0,0 +1,16 @@
+/// Connects to the server at the given address.
+///
+/// Retries up to three times before giving up.
+#[docify::export]
+fn connect(address: &str) {
+    /// Not part of the embedded docs.
+    let _ = address;
+}
+
+#[docify::export]
+#[doc = "Closes the connection."]
+#[doc = "  Indented continuation."]
+fn disconnect() {}
+
+#[docify::export]
+fn undocumented() {}
@@ -0,0 +1,26 @@
+#[docify::export]
+macro_rules! square {
+    ($x:expr) => {
+        $x * $x
+    };
+    ($x:expr, $($rest:expr),+) => {
+        (square!($x), square!($($rest),+))
+    };
+}
+
+#[docify::export(max_example)]
+macro_rules! max {
+    ($x:expr) => { $x };
+    ($x:expr, $($y:expr),+) => {{
+        let rest = max!($($y),+);
+        if $x > rest { $x } else { rest }
+    }};
+}
+
+#[macro_export]
+#[docify::export]
+macro_rules! url {
+    ($host:literal) => {
+        concat!("https://", $host) // a scheme with slashes
+    };
+}
@@ -0,0 +1,8 @@
+#[docify::export(123)]
+fn numeric() {}
+
+#[docify::export(valid)]
+fn valid_fn() {}
+
+#[docify::export()]
+fn empty_parens() {}
@@ -0,0 +1,13 @@
+#[docify::export]
+mod example {
+    use std::collections::HashMap;
+
+    pub fn build() -> HashMap<&'static str, u32> {
+        let mut map = HashMap::new();
+        map.insert("answer", 42);
+        map
+    }
+}
+
+#[docify::export]
+struct NoContents;
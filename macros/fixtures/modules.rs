@@ -0,0 +1,26 @@
+mod client {
+    #[docify::export]
+    pub fn run() {
+        println!("client");
+    }
+
+    pub struct Client;
+
+    impl Client {
+        pub fn connect(&self) {}
+    }
+}
+
+mod server {
+    #[docify::export]
+    pub fn run() {
+        println!("server");
+    }
+
+    mod nested {
+        #[docify::export]
+        pub fn run() {
+            println!("nested");
+        }
+    }
+}
@@ -0,0 +1,17 @@
+#[docify::export]
+mod outer {
+    #[docify::export]
+    fn first() {}
+
+    #[docify::export(second_fn)] fn second() {}
+
+    /// mentions `#[docify::export]` in its docs
+    fn third() {}
+
+    #[docify::export_content]
+    mod inner {
+        #[test]
+        #[docify::export]
+        fn fourth() {}
+    }
+}
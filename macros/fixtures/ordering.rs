@@ -0,0 +1,29 @@
+#[derive(Debug)]
+#[allow(dead_code)]
+#[docify::export(Ordered)]
+#[derive(Clone)]
+struct First;
+
+mod outer {
+    #[docify::export(Ordered)]
+    pub fn second() {
+        #[docify::export(Ordered)]
+        fn third() {}
+        third();
+    }
+
+    mod inner {
+        #[cfg_attr(test, allow(unused))]
+        /// The fourth item.
+        #[docify::export(Ordered)]
+        pub struct Fourth;
+    }
+}
+
+#[docify::export(Ordered)]
+const FIFTH: u8 = 5;
+
+#[docify::export(Other)]
+#[inline]
+#[docify::export(Ordered)]
+fn sixth() {}
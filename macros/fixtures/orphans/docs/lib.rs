@@ -0,0 +1,5 @@
+/// Embedding examples with `docify::embed!(..)`:
+#[doc = docify::embed!("fixtures/orphans/examples/basics.rs", hello)]
+#[doc = docify::embed_run!("fixtures/orphans/examples/basics.rs", client::connect, show_source)]
+#[doc = docify::embed!("fixtures/orphans/examples/files/whole.rs")]
+pub struct Docs;
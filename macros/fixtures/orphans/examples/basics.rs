@@ -0,0 +1,13 @@
+#[docify::export]
+fn hello() {}
+
+#[docify::export(greeting)]
+fn greet() {}
+
+#[docify::export]
+fn forgotten() {}
+
+mod client {
+    #[docify::export]
+    fn connect() {}
+}
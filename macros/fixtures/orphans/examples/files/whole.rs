@@ -0,0 +1,2 @@
+#[docify::export]
+fn only_embedded_as_a_file() {}
@@ -0,0 +1,9 @@
+
+
+// leading and trailing blank lines are trimmed
+fn padded() {
+
+    let x = 1;
+}
+
+
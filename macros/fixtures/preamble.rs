@@ -0,0 +1,19 @@
+fn unrelated() {}
+
+// Connects to the server and sends a greeting.
+// Retries are handled by the client.
+#[docify::export]
+fn greet() {
+    println!("hello");
+}
+
+mod nested {
+    /*
+     * A block comment describing the example.
+     */
+    /// Adds one.
+    #[docify::export]
+    fn add_one(x: u32) -> u32 {
+        x + 1
+    }
+}
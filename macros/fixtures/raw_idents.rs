@@ -0,0 +1,10 @@
+#[docify::export]
+pub struct r#type {
+    pub r#match: bool,
+}
+
+#[docify::export(r#loop)]
+fn run_forever() {}
+
+#[docify::export]
+fn plain() {}
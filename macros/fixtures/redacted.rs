@@ -0,0 +1,7 @@
+#[docify::export]
+fn connect() {
+    // docify:redact-start
+    const API_KEY: &str = "sk-live-1234567890";
+    // docify:redact-end
+    let client = Client::new(API_KEY);
+}
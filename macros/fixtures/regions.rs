@@ -0,0 +1,21 @@
+fn handle(request: Request) -> Response {
+    match request.kind {
+        // docify:region-start arms
+        Kind::Get => fetch(request),
+        // docify:region-start writes
+        Kind::Post => create(request),
+        Kind::Put => update(request),
+        // docify:region-end
+        // docify:region-end
+        Kind::Delete => remove(request),
+    }
+}
+
+fn payload() -> Vec<u8> {
+    let body = vec![
+        // docify:region-start writes
+        0x01, 0x02,
+        // docify:region-end
+    ];
+    body
+}
@@ -0,0 +1,38 @@
+#[docify::export]
+#[inline]
+pub fn generic_fn<T: Clone>(value: &T, count: usize) -> Vec<T>
+where
+    T: std::fmt::Debug,
+{
+    vec![value.clone(); count]
+}
+
+pub trait Greeter {
+    #[docify::export]
+    fn greet(&self) -> String {
+        String::from("hello {")
+    }
+}
+
+#[docify::export]
+pub struct NotAFunction;
+
+#[docify::export]
+pub trait Storage {
+    /// The key type.
+    type Key;
+
+    fn get(&self, key: &Self::Key) -> Option<String>;
+
+    // Returns `true` if the key exists.
+    fn contains(&self, key: &Self::Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    fn len_where<T>(&self) -> usize
+    where
+        T: Default,
+    {
+        0
+    }
+}
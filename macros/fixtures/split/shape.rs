@@ -0,0 +1,4 @@
+#[docify::export(Shape)]
+pub trait Shape {
+    fn area(&self) -> f64;
+}
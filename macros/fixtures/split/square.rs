@@ -0,0 +1,8 @@
+pub struct Square(pub f64);
+
+#[docify::export(Shape)]
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+}
@@ -0,0 +1,2 @@
+#[docify::export]
+pub fn unrelated() {}
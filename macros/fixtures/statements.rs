@@ -0,0 +1,11 @@
+fn setup() {
+    let unrelated = 1;
+    #[docify::export(create_client)]
+    let client = Client::new("localhost");
+    #[docify::export]
+    let config: Config = Config::default();
+    #[docify::export(connect)]
+    client.connect(&config);
+    #[docify::export]
+    println!("connected!");
+}
@@ -0,0 +1,18 @@
+#[docify::export]
+struct Unit;
+
+#[docify::export]
+struct Tuple(u32, pub String);
+
+#[docify::export]
+struct Wrapper<T>(T)
+where
+    T: Copy;
+
+#[docify::export]
+struct Named {
+    field: u32,
+}
+
+#[docify::export]
+struct Spaced ;
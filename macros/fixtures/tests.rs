@@ -0,0 +1,15 @@
+#[docify::export]
+#[test]
+#[should_panic(expected = "empty")]
+fn example_sync() {
+    let items: Vec<u32> = Vec::new();
+    items.first().expect("empty");
+}
+
+#[docify::export]
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(miri, ignore)]
+async fn example_async() {
+    let value = fetch().await;
+    assert_eq!(value, 1);
+}
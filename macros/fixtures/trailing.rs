@@ -0,0 +1,6 @@
+#[docify::export]
+fn padded() {   
+    let banner = "first   
+    second";   
+	println!("{}", banner);	
+}
@@ -0,0 +1,3 @@
+fn step_two() {
+    println!("two");
+}
@@ -0,0 +1,6 @@
+const GREETING: &str = "héllo wörld ├├├";
+
+#[docify::export]
+fn after_unicode() {
+    println!("{}", GREETING);
+}
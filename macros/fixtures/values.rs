@@ -0,0 +1,23 @@
+pub struct Config {
+    pub name: &'static str,
+    pub retries: u32,
+}
+
+mod settings {
+    use super::Config;
+
+    #[docify::export]
+    pub static CONFIG: Config = Config {
+        name: "docify",
+        retries: 3, // per request
+    };
+}
+
+#[docify::export]
+const LIMIT: u32 = 10 * 3;
+
+fn main() {
+    #[docify::export]
+    let ports: [u16; 3] = [8080, 8081, 8082];
+    println!("{:?}", ports);
+}
@@ -0,0 +1,10 @@
+pub enum Message {
+    Ping,
+    /// Sends some data.
+    #[allow(unused)]
+    Data {
+        id: u32,
+        payload: Vec<u8>,
+    },
+    Close(u16),
+}
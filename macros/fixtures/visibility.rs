@@ -0,0 +1,37 @@
+fn helper() -> u32 {
+    1
+}
+
+#[docify::export]
+mod api {
+    use std::collections::HashMap;
+
+    /// Creates a new registry.
+    pub fn new_registry() -> Registry {
+        Registry::default()
+    }
+
+    /// Only used internally.
+    fn helper() -> u32 {
+        1
+    }
+
+    #[derive(Default)]
+    pub struct Registry {
+        entries: HashMap<String, u32>,
+    }
+
+    impl Registry {
+        pub(crate) fn len(&self) -> usize {
+            self.entries.len()
+        }
+    }
+
+    pub mod errors {
+        pub struct NotFound;
+
+        struct Internal;
+    }
+
+    const LIMIT: usize = 10;
+}
@@ -0,0 +1,9 @@
+mod wide {
+    #[docify::export]
+    fn wide() {
+        /// This doc comment is far too long to fit within a narrow column of rendered docs.
+        let value = 1;
+        // Short comment.
+        let very_long_line_of_code_that_is_not_wrapped = value + value + value + value + value;
+    }
+}
@@ -0,0 +1,10 @@
+// Copyright (c) Example Authors
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+// docify:start
+fn main() {
+    let mut map = HashMap::new();
+    map.insert(1, "one");
+}
@@ -2,49 +2,49 @@
 
 use common_path::common_path;
 use derive_syn_parse::Parse;
-use once_cell::sync::Lazy;
+use once_cell::{sync::Lazy, unsync::OnceCell};
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::{quote, ToTokens};
+use quote::{quote, quote_spanned, ToTokens};
 use regex::Regex;
 use std::{
-    cmp::min,
-    collections::HashMap,
+    cell::RefCell,
+    cmp::{max, min},
+    collections::{HashMap, HashSet},
     fs::{self, OpenOptions},
     io::Write,
+    ops::Range,
     path::{Path, PathBuf},
+    rc::Rc,
     str::FromStr,
+    time::SystemTime,
 };
 use syn::{
+    braced, bracketed,
+    ext::IdentExt,
+    parse::{Parse, ParseStream, Parser},
     parse2,
+    punctuated::Punctuated,
     spanned::Spanned,
-    token::Paren,
+    token::{Brace, Bracket, Paren},
     visit::{self, Visit},
-    AttrStyle, Attribute, Error, File, Ident, ImplItem, Item, LitStr, Meta, Result, Token,
-    TraitItem,
+    AttrStyle, Attribute, BinOp, Block, Error, Expr, ExprLit, File, ForeignItem, Ident, ImplItem,
+    ImplItemConst, ImplItemFn, Item, ItemConst, ItemEnum, ItemFn, ItemImpl, ItemMod, ItemStatic,
+    ItemTrait, ItemUse, Lit, LitInt, LitStr, Macro, Meta, Pat, Path as SynPath, RangeLimits,
+    Result, Stmt, Token, TraitItem, TraitItemFn, Type, UnOp, UseTree, Visibility,
 };
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 use toml::{Table, Value};
 use walkdir::WalkDir;
 
+/// Returns the byte position of the start of the line containing `pos` within `source`.
 fn line_start_position<S: AsRef<str>>(source: S, pos: usize) -> usize {
     let source = source.as_ref();
-    if source.len() <= pos {
-        panic!(
-            "The specified position ({}) is longer than the source string length ({}).",
-            pos,
-            source.len()
-        );
-    }
-    let mut cursor = 0;
-    for line in source.lines() {
-        cursor += line.len();
-        if cursor > pos {
-            return cursor - line.len();
-        }
-        cursor += 1; // '\n'
+    let pos = min(pos, source.len());
+    match source[..pos].rfind('\n') {
+        Some(newline) => newline + 1,
+        None => 0,
     }
-    unreachable!()
 }
 
 fn fix_leading_indentation<S: AsRef<str>>(source: S) -> String {
@@ -67,7 +67,9 @@ fn fix_leading_indentation<S: AsRef<str>>(source: S) -> String {
     let mut output_lines = source
         .lines()
         .map(|line| {
-            if line.len() >= shared_indent {
+            if line.trim().is_empty() {
+                String::new() // don't leave behind any stray whitespace on blank lines
+            } else if line.len() >= shared_indent {
                 line[shared_indent..].to_string()
             } else {
                 line.to_string()
@@ -86,8 +88,7 @@ fn fix_leading_indentation<S: AsRef<str>>(source: S) -> String {
 fn fix_indentation<S: AsRef<str>>(source: S) -> String {
     let source = source.as_ref();
     // let source = fix_first_line_indentation(source);
-    let source = fix_leading_indentation(source);
-    source
+    fix_leading_indentation(source)
 }
 
 fn caller_crate_root() -> Option<PathBuf> {
@@ -110,7 +111,7 @@ fn caller_crate_root() -> Option<PathBuf> {
         if !file_name.eq_ignore_ascii_case("Cargo.toml") {
             continue;
         }
-        let Ok(cargo_toml) = std::fs::read_to_string(&entry.path()) else {
+        let Ok(cargo_toml) = std::fs::read_to_string(entry.path()) else {
             continue;
         };
         let Ok(table) = Table::from_str(cargo_toml.as_str()) else {
@@ -129,6 +130,36 @@ fn caller_crate_root() -> Option<PathBuf> {
     None
 }
 
+/// Resolves the directory that `docify::embed!(..)` paths are relative to, which is the
+/// `CARGO_MANIFEST_DIR` of the crate being compiled, falling back to the current working
+/// directory if that variable is not set.
+fn manifest_dir() -> Option<PathBuf> {
+    match std::env::var("CARGO_MANIFEST_DIR") {
+        Ok(dir) => Some(PathBuf::from(dir)),
+        Err(_) => std::env::current_dir().ok(),
+    }
+}
+
+/// Finds the root directory of the cargo workspace containing the crate being compiled, i.e.
+/// the outermost ancestor of [`manifest_dir`] containing a `Cargo.toml` with a `[workspace]`
+/// table. If the crate is not part of a workspace, this is just the [`manifest_dir`].
+fn workspace_root() -> Option<PathBuf> {
+    let manifest_dir = manifest_dir()?;
+    let mut root = manifest_dir.clone();
+    for dir in manifest_dir.ancestors() {
+        let Ok(cargo_toml) = fs::read_to_string(dir.join("Cargo.toml")) else {
+            continue;
+        };
+        let Ok(table) = Table::from_str(cargo_toml.as_str()) else {
+            continue;
+        };
+        if table.contains_key("workspace") {
+            root = dir.to_path_buf();
+        }
+    }
+    Some(root)
+}
+
 /// Prettifies a long path so that leading segments other than the crate root are ignored
 ///
 /// NOTE: unwraps [`caller_crate_root`], as you would only use this if that has already
@@ -146,14 +177,25 @@ fn prettify_path<P: AsRef<Path>>(path: P) -> PathBuf {
         .collect::<PathBuf>()
 }
 
-const DOCIFYING: &'static str = "   Docifying ";
+const DOCIFYING: &str = "   Docifying ";
+const DOCIFY_WARNING: &str = "     Warning ";
 
 /// Tries to write the specified string to the terminal in green+bold. Falls back to normal
 /// `print!()`. Function is infallible.
 fn write_green<S: AsRef<str>>(st: S) {
+    write_colored(st, Color::Green);
+}
+
+/// Like [`write_green`], but in yellow+bold, for warnings about possible mistakes.
+fn write_yellow<S: AsRef<str>>(st: S) {
+    write_colored(st, Color::Yellow);
+}
+
+/// Tries to write the specified string to the terminal in the specified color, in bold.
+fn write_colored<S: AsRef<str>>(st: S, color: Color) {
     let mut stdout = StandardStream::stdout(ColorChoice::Always);
-    let _ = stdout.set_color(ColorSpec::new().set_fg(Some(Color::Green)).set_bold(true));
-    if let Err(_) = write!(&mut stdout, "{}", st.as_ref()) {
+    let _ = stdout.set_color(ColorSpec::new().set_fg(Some(color)).set_bold(true));
+    if write!(&mut stdout, "{}", st.as_ref()).is_err() {
         print!("{}", st.as_ref());
     }
     let _ = stdout.set_color(ColorSpec::new().set_fg(None).set_bold(false));
@@ -180,15 +222,32 @@ impl NamedItem for Item {
             Item::TraitAlias(item_trait_alias) => Some(item_trait_alias.ident.clone()),
             Item::Type(item_type) => Some(item_type.ident.clone()),
             Item::Union(item_union) => Some(item_union.ident.clone()),
+            Item::Impl(item_impl) => impl_name_ident(item_impl),
             // Item::ForeignMod(item_foreign_mod) => None,
             // Item::Use(item_use) => None,
-            // Item::Impl(item_impl) => None,
             // Item::Verbatim(_) => None,
             _ => None,
         }
     }
 }
 
+/// Derives a name for an `impl` block from the last segment of its self type and trait, i.e.
+/// `Foo_Display_impl` for `impl Display for Foo` or `Foo_impl` for `impl Foo`. Returns `None` if
+/// the self type isn't a path.
+fn impl_name_ident(item_impl: &ItemImpl) -> Option<Ident> {
+    let Type::Path(type_path) = &*item_impl.self_ty else {
+        return None;
+    };
+    let self_ty = &type_path.path.segments.last()?.ident;
+    let name = match &item_impl.trait_ {
+        Some((_, trait_path, _)) => {
+            format!("{}_{}_impl", self_ty, trait_path.segments.last()?.ident)
+        }
+        None => format!("{}_impl", self_ty),
+    };
+    Some(Ident::new(&name, self_ty.span()))
+}
+
 impl NamedItem for ImplItem {
     fn name_ident(&self) -> Option<Ident> {
         match self {
@@ -215,6 +274,38 @@ impl NamedItem for TraitItem {
     }
 }
 
+impl NamedItem for ForeignItem {
+    fn name_ident(&self) -> Option<Ident> {
+        match self {
+            ForeignItem::Fn(foreign_item_fn) => Some(foreign_item_fn.sig.ident.clone()),
+            ForeignItem::Static(foreign_item_static) => Some(foreign_item_static.ident.clone()),
+            ForeignItem::Type(foreign_item_type) => Some(foreign_item_type.ident.clone()),
+            // ForeignItem::Macro(foreign_item_macro) => None,
+            // ForeignItem::Verbatim(foreign_item_verbatim) => None,
+            _ => None,
+        }
+    }
+}
+
+impl NamedItem for Stmt {
+    fn name_ident(&self) -> Option<Ident> {
+        match self {
+            Stmt::Local(local) => match &local.pat {
+                Pat::Ident(pat_ident) => Some(pat_ident.ident.clone()),
+                Pat::Type(pat_type) => match &*pat_type.pat {
+                    Pat::Ident(pat_ident) => Some(pat_ident.ident.clone()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            Stmt::Macro(stmt_macro) => stmt_macro.mac.path.get_ident().cloned(),
+            // Stmt::Item(item) => None, (handled directly by `visit_item`)
+            // Stmt::Expr(expr, semi) => None,
+            _ => None,
+        }
+    }
+}
+
 /// Generalizes over items that have some underlying set of [`Attribute`] associated with them.
 trait AttributedItem {
     /// Gets a reference to the underlying [`Vec`] of [`Attribute`]s for this item, if
@@ -280,7 +371,7 @@ impl AttributedItem for ImplItem {
             ImplItem::Type(impl_item_type) => &impl_item_type.attrs,
             ImplItem::Macro(impl_item_macro) => &impl_item_macro.attrs,
             // ImplItem::Verbatim(impl_item_verbatim) => &EMPTY,
-            _ => &EMPTY,
+            _ => EMPTY,
         }
     }
 
@@ -305,7 +396,7 @@ impl AttributedItem for TraitItem {
             TraitItem::Type(trait_item_type) => &trait_item_type.attrs,
             TraitItem::Macro(trait_item_macro) => &trait_item_macro.attrs,
             // TraitItem::Verbatim(trait_item_verbatim) => &EMPTY,
-            _ => &EMPTY,
+            _ => EMPTY,
         }
     }
 
@@ -321,6 +412,81 @@ impl AttributedItem for TraitItem {
     }
 }
 
+impl AttributedItem for ForeignItem {
+    fn item_attributes(&self) -> &Vec<Attribute> {
+        const EMPTY: &Vec<Attribute> = &Vec::new();
+        match self {
+            ForeignItem::Fn(foreign_item_fn) => &foreign_item_fn.attrs,
+            ForeignItem::Static(foreign_item_static) => &foreign_item_static.attrs,
+            ForeignItem::Type(foreign_item_type) => &foreign_item_type.attrs,
+            ForeignItem::Macro(foreign_item_macro) => &foreign_item_macro.attrs,
+            // ForeignItem::Verbatim(foreign_item_verbatim) => &EMPTY,
+            _ => EMPTY,
+        }
+    }
+
+    fn set_item_attributes(&mut self, attrs: Vec<Attribute>) {
+        match self {
+            ForeignItem::Fn(foreign_item_fn) => foreign_item_fn.attrs = attrs,
+            ForeignItem::Static(foreign_item_static) => foreign_item_static.attrs = attrs,
+            ForeignItem::Type(foreign_item_type) => foreign_item_type.attrs = attrs,
+            ForeignItem::Macro(foreign_item_macro) => foreign_item_macro.attrs = attrs,
+            // ForeignItem::Verbatim(foreign_item_verbatim) => unimplemented!(),
+            _ => unimplemented!(),
+        }
+    }
+}
+
+impl AttributedItem for Stmt {
+    fn item_attributes(&self) -> &Vec<Attribute> {
+        const EMPTY: &Vec<Attribute> = &Vec::new();
+        match self {
+            Stmt::Local(local) => &local.attrs,
+            Stmt::Macro(stmt_macro) => &stmt_macro.attrs,
+            Stmt::Expr(expr, _) => match expr {
+                Expr::Assign(e) => &e.attrs,
+                Expr::Block(e) => &e.attrs,
+                Expr::Call(e) => &e.attrs,
+                Expr::ForLoop(e) => &e.attrs,
+                Expr::If(e) => &e.attrs,
+                Expr::Loop(e) => &e.attrs,
+                Expr::Macro(e) => &e.attrs,
+                Expr::Match(e) => &e.attrs,
+                Expr::MethodCall(e) => &e.attrs,
+                Expr::Unsafe(e) => &e.attrs,
+                Expr::While(e) => &e.attrs,
+                _ => EMPTY,
+            },
+            // items are visited separately via `visit_item`, so we don't want to report their
+            // attributes here as well or they would be matched twice
+            // Stmt::Item(item) => EMPTY,
+            _ => EMPTY,
+        }
+    }
+
+    fn set_item_attributes(&mut self, attrs: Vec<Attribute>) {
+        match self {
+            Stmt::Local(local) => local.attrs = attrs,
+            Stmt::Macro(stmt_macro) => stmt_macro.attrs = attrs,
+            Stmt::Expr(expr, _) => match expr {
+                Expr::Assign(e) => e.attrs = attrs,
+                Expr::Block(e) => e.attrs = attrs,
+                Expr::Call(e) => e.attrs = attrs,
+                Expr::ForLoop(e) => e.attrs = attrs,
+                Expr::If(e) => e.attrs = attrs,
+                Expr::Loop(e) => e.attrs = attrs,
+                Expr::Macro(e) => e.attrs = attrs,
+                Expr::Match(e) => e.attrs = attrs,
+                Expr::MethodCall(e) => e.attrs = attrs,
+                Expr::Unsafe(e) => e.attrs = attrs,
+                Expr::While(e) => e.attrs = attrs,
+                _ => unimplemented!(),
+            },
+            _ => unimplemented!(),
+        }
+    }
+}
+
 /// Marks an item for export, making it available for embedding as a rust doc example via
 /// [`docify::embed!(..)`](`macro@embed`) or [`docify::embed_run!(..)`](`macro@embed_run`).
 ///
@@ -335,12 +501,16 @@ impl AttributedItem for TraitItem {
 /// ```
 ///
 /// When you [`docify::embed!(..)`](`macro@embed`) this item, you will have to refer to it by
-/// the primary ident associated with the item, in this case `some_item`. In some cases, such
-/// as with `impl` statements, there is no clear main ident. You should handle these situations
-/// by specifying an ident manually (not doing so will result in a compile error):
+/// the primary ident associated with the item, in this case `some_item`. `impl` blocks have no
+/// ident of their own, so one is derived from the last segment of the self type and, for trait
+/// impls, of the trait: `impl Display for Foo` is exported as `Foo_Display_impl`, while
+/// `impl Foo` is exported as `Foo_impl`. Generic arguments are ignored, so for example both
+/// `impl From<u8> for Foo` and `impl From<u16> for Foo` are exported as `Foo_From_impl`. If this
+/// would be ambiguous, or the self type isn't a plain path (such as a reference or a tuple), you
+/// should specify an ident manually (not doing so will result in a compile error):
 /// ```ignore
 /// #[docify::export(some_name)]
-/// impl SomeTrait for Something {
+/// impl SomeTrait for &Something {
 ///     // ...
 /// }
 /// ```
@@ -359,16 +529,69 @@ impl AttributedItem for TraitItem {
 /// [`docify::embed_run!(..)`](`macro@embed_run`) such an item, you must refer to it by
 /// `SomeName` (in this case), or whatever name you provided to `#[docify::export]`.
 ///
+/// Several comma-separated export names can be specified to make the same item available
+/// under each of them, i.e. `#[docify::export(setup, setup_example)]`. The item can then be
+/// embedded by any one of these names, though no longer by its inherent ident unless that is
+/// also listed. Export names must be plain idents, otherwise a compile error is issued both by
+/// the attribute itself and by any [`docify::embed!(..)`](`macro@embed`) of the same file. The
+/// latter also rejects empty parentheses such as `#[docify::export()]`, which are most likely
+/// a mistake.
+///
+/// Items can also be given a category, i.e. `#[docify::export(category = "networking")]` or
+/// `#[docify::export(fetch, category = "networking")]`, so that every item of that category
+/// can be embedded at once using the `category` option of
+/// [`docify::embed!(..)`](`macro@embed`). Likewise, a description can be given, i.e.
+/// `#[docify::export(setup, desc = "Sets up the client")]`, which is shown above the example
+/// by the `show_desc` option of [`docify::embed!(..)`](`macro@embed`).
+///
+/// Crates that only depend on docify under a feature can export items conditionally, i.e.
+/// `#[cfg_attr(feature = "docs", docify::export(my_name))]`. Such items are found regardless
+/// of the condition, unless the `respect_cfg` option of [`docify::embed!(..)`](`macro@embed`)
+/// is specified, in which case the condition is evaluated just like a `#[cfg(..)]` attribute.
+/// The whole `cfg_attr` attribute is left out of the embedded example.
+///
 /// There is no guard to prevent duplicate export names in the same file, and export names are
 /// all considered within the global namespace of the file in question (they do not exist
-/// inside a particular module or scope within a source file). When using
+/// inside a particular module or scope within a source file), unless the item is embedded by a
+/// module path such as `client::run`. When using
 /// [`docify::embed!(..)`](`macro@embed`), duplicate results are simply embedded one after
-/// another, and this is by design.
+/// another, and this is by design. If you would rather be notified of duplicates, pass the
+/// `unique` option to [`docify::embed!(..)`](`macro@embed`).
 ///
 /// If there are multiple items with the same inherent name in varipous scopes in the same
 /// file, and you want to export just one of them as a doc example, you should specify a unique
 /// ident as the export name for this item.
 ///
+/// Items defined inside a function body (or a closure), such as a helper `fn`, can be exported
+/// just like top-level items. Individual statements within a function body, such as `let`
+/// bindings, method calls, and macro invocations, can also be exported. A `let` binding with a
+/// simple pattern can be referred to by the ident it binds, while other statements require a
+/// manual export name:
+/// ```ignore
+/// fn setup() {
+///     #[docify::export(create_client)]
+///     let client = Client::new("localhost");
+///     client.connect();
+/// }
+/// ```
+///
+/// Keep in mind that attribute macros on statements are not yet supported by stable rust, so
+/// this is mainly useful for source files that are not themselves compiled, or on nightly. On
+/// stable, statements can instead be wrapped in [`docify::export_stmt!(..)`](`macro@export_stmt`).
+///
+/// Items that docify can only embed in a limited way, such as `extern` blocks and items using
+/// syntax that docify can't parse, are exported as usual, but with a compile warning at the
+/// item explaining the limitation.
+///
+/// Declarations inside an `extern` block can also be exported individually, so that a single
+/// FFI function can be embedded on its own:
+/// ```ignore
+/// extern "C" {
+///     #[docify::export]
+///     fn abs(x: i32) -> i32;
+/// }
+/// ```
+///
 /// Note that if you wish to embed an _entire_ file, you don't need `#[docify::export]` at all
 /// and can instead specify just a path to [`docify::embed!(..)`](`macro@embed`) or
 /// [`docify::embed_run!(..)`](`macro@embed_run`).
@@ -423,10 +646,129 @@ pub fn export_content(attr: TokenStream, tokens: TokenStream) -> TokenStream {
     }
 }
 
+/// Marks a sequence of statements for embedding under the specified name, for use where
+/// [`#[docify::export]`](`macro@export`) can't be attached to statements on stable rust:
+/// ```ignore
+/// fn setup() {
+///     docify::export_stmt!(create_client, {
+///         let client = Client::new("localhost");
+///         client.connect();
+///     });
+///     client.send("hello");
+/// }
+/// ```
+///
+/// The statements are expanded in place, without the surrounding braces, so bindings such as
+/// `client` remain in scope afterwards. Embedding `create_client` with
+/// [`docify::embed!(..)`](`macro@embed`) then embeds just the wrapped statements.
+#[proc_macro]
+pub fn export_stmt(tokens: TokenStream) -> TokenStream {
+    match export_stmt_internal(tokens) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Hides an item from whole-file embeds, i.e. [`docify::embed!(..)`](`macro@embed`) calls with
+/// no `item_ident`, such as a helper `main()` function or a test module:
+/// ```ignore
+/// fn example() {
+///     println!("shown");
+/// }
+///
+/// #[docify::hide]
+/// fn main() {
+///     example();
+/// }
+/// ```
+///
+/// Embedding this file in its entirety would only show `example`. The attribute must be
+/// written as `#[docify::hide]` to be recognized, and it has no effect on the item otherwise.
+#[proc_macro_attribute]
+pub fn hide(attr: TokenStream, tokens: TokenStream) -> TokenStream {
+    match hide_internal(attr, tokens) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Internal implementation for `#[hide]`
+fn hide_internal(
+    attr: impl Into<TokenStream2>,
+    tokens: impl Into<TokenStream2>,
+) -> Result<TokenStream2> {
+    let attr = attr.into();
+    if !attr.is_empty() {
+        return Err(Error::new_spanned(
+            attr,
+            "The #[docify::hide] attribute does not take any arguments.",
+        ));
+    }
+    let item = parse2::<Item>(tokens.into())?;
+    Ok(quote!(#item))
+}
+
 /// Used to parse args for `#[export(..)]`
-#[derive(Parse)]
 struct ExportAttr {
-    ident: Option<Ident>,
+    idents: Vec<Ident>,
+    /// The `category = ".."` of the exported item, by which it can be embedded along with the
+    /// other items of the same category.
+    category: Option<LitStr>,
+    /// The `desc = ".."` of the exported item, a description that can be shown above it when it
+    /// is embedded.
+    desc: Option<LitStr>,
+}
+
+impl Parse for ExportAttr {
+    /// Parses the comma-separated export names and `key = ".."` metadata of an `#[export(..)]`
+    /// attribute, issuing an error that points at the offending tokens if any of the names is
+    /// not a plain ident.
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut idents = Vec::new();
+        let mut category = None;
+        let mut desc = None;
+        while !input.is_empty() {
+            if input.peek(Ident) && input.peek2(Token![=]) {
+                let key = input.parse::<Ident>()?;
+                input.parse::<Token![=]>()?;
+                match key.to_string().as_str() {
+                    "category" => category = Some(input.parse::<LitStr>()?),
+                    "desc" => desc = Some(input.parse::<LitStr>()?),
+                    _ => {
+                        return Err(Error::new(
+                            key.span(),
+                            format!(
+                                "Unknown docify export metadata `{}`, only `category = \"..\"` \
+                                and `desc = \"..\"` are supported.",
+                                key
+                            ),
+                        ))
+                    }
+                }
+            } else if input.peek(Ident) {
+                idents.push(input.parse::<Ident>()?);
+            } else {
+                let invalid = input.cursor().token_tree().map(|(tt, _)| tt);
+                return Err(Error::new(
+                    input.span(),
+                    format!(
+                        "Invalid docify export name `{}`, expected an ident, i.e. \
+                        #[docify::export(my_name)].",
+                        invalid.map(|tt| tt.to_string()).unwrap_or_default(),
+                    ),
+                ));
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(ExportAttr {
+            idents,
+            category,
+            desc,
+        })
+    }
 }
 
 /// Internal implementation for `#[export]`
@@ -435,19 +777,116 @@ fn export_internal(
     tokens: impl Into<TokenStream2>,
 ) -> Result<TokenStream2> {
     let attr = parse2::<ExportAttr>(attr.into())?;
-    let item = parse2::<Item>(tokens.into())?;
+    let tokens = tokens.into();
+    // declarations inside `extern` blocks don't parse as regular items, i.e. `fn foo();`
+    if let Some(foreign_item) = parse_foreign_item(&tokens) {
+        attr.idents
+            .into_iter()
+            .next()
+            .or_else(|| foreign_item.name_ident())
+            .ok_or_else(|| {
+                Error::new(
+                    foreign_item.span(),
+                    "Cannot automatically detect ident from this item. \
+                You will need to specify a name manually as the argument \
+                for the #[export] attribute, i.e. #[export(my_name)].",
+                )
+            })?;
+        return Ok(quote!(#foreign_item));
+    }
+    let item = parse2::<Item>(tokens)?;
 
     // get export ident
-    let _export_ident = attr.ident.or_else(|| item.name_ident()).ok_or_else(|| {
-        Error::new(
-            item.span(),
-            "Cannot automatically detect ident from this item. \
+    let _export_ident = attr
+        .idents
+        .into_iter()
+        .next()
+        .or_else(|| item.name_ident())
+        .ok_or_else(|| {
+            Error::new(
+                item.span(),
+                "Cannot automatically detect ident from this item. \
             You will need to specify a name manually as the argument \
             for the #[export] attribute, i.e. #[export(my_name)].",
-        )
-    })?;
+            )
+        })?;
 
-    Ok(quote!(#item))
+    match export_site_warning(&item) {
+        Some(warning) => Ok(quote!(#item #warning)),
+        None => Ok(quote!(#item)),
+    }
+}
+
+/// Parses the specified tokens as a declaration from inside an `extern` block, i.e.
+/// `fn foo();`, returning `None` for anything that is also a regular item.
+fn parse_foreign_item(tokens: &TokenStream2) -> Option<ForeignItem> {
+    match parse2::<ForeignItem>(tokens.clone()).ok()? {
+        foreign_item @ (ForeignItem::Fn(_) | ForeignItem::Static(_) | ForeignItem::Type(_)) => {
+            Some(foreign_item)
+        }
+        _ => None,
+    }
+}
+
+/// Returns a compile warning, located at the specified item, if it is of a kind that can only
+/// be embedded in a limited way, so that this is noticed where the item is exported rather
+/// than only once the embedded example looks wrong in another file.
+///
+/// Stable rust offers proc macros no way to emit warnings, so the warning is raised by using
+/// a `#[deprecated]` item, whose note is the actual warning.
+fn export_site_warning(item: &Item) -> Option<TokenStream2> {
+    let note = match item {
+        Item::Verbatim(_) => {
+            "docify: this item uses syntax that docify can't parse, so it can only be embedded \
+            as-is, and options that depend on its structure, such as `signature_only` and \
+            `sort = \"name\"`, will not apply to it."
+        }
+        Item::ForeignMod(_) => {
+            "docify: `extern` blocks have no name of their own, so this block can only be \
+            embedded as a whole by its export name, and options that depend on the name of an \
+            item, such as `sort = \"name\"`, will not apply to it. To embed a single \
+            declaration, export it inside the block instead."
+        }
+        _ => return None,
+    };
+    Some(quote_spanned! {item.span()=>
+        const _: () = {
+            #[deprecated(note = #note)]
+            #[allow(non_camel_case_types)]
+            struct docify_export_warning;
+            let _ = docify_export_warning;
+        };
+    })
+}
+
+/// Internal implementation behind [`macro@export_stmt`].
+fn export_stmt_internal(tokens: impl Into<TokenStream2>) -> Result<TokenStream2> {
+    let args = parse2::<ExportStmtArgs>(tokens.into())?;
+    let stmts = Block::parse_within.parse2(args.stmts)?;
+    Ok(quote!(#(#stmts)*))
+}
+
+/// Used to parse args for `docify::export_stmt!(..)`
+#[derive(Parse)]
+struct ExportStmtArgs {
+    name: Ident,
+    _comma: Token![,],
+    #[brace]
+    _brace: Brace,
+    #[inside(_brace)]
+    stmts: TokenStream2,
+}
+
+/// Returns `true` if the specified macro path refers to `docify::export_stmt!`.
+fn is_export_stmt(path: &SynPath) -> bool {
+    let mut segments = path.segments.iter().rev();
+    match (segments.next(), segments.next()) {
+        (Some(last), None) => last.ident == "export_stmt",
+        (Some(last), Some(second_to_last)) => {
+            last.ident == "export_stmt" && second_to_last.ident == "docify"
+        }
+        _ => false,
+    }
 }
 
 /// Embeds the specified item from the specified source file in a rust doc example, with pretty
@@ -463,11 +902,12 @@ fn export_internal(
 /// ```
 ///
 /// Which will expand to the `my_example` item in `path/to/file.rs` being embedded in a rust
-/// doc example marked with `ignore`. If you want to have your example actually run in rust
+/// doc example marked with `rust,ignore`. If you want to have your example actually run in rust
 /// docs as well, you should use [`docify::embed_run!(..)`](`macro@embed_run`).
 ///
 /// ### Arguments
-/// - `source_path`: the file path (relative to the current crate root) that contains the item
+/// - `source_path`: the file path (relative to the current crate root, i.e. the
+///   `CARGO_MANIFEST_DIR` of the crate being compiled) that contains the item
 ///   you would like to embed, represented as a string literal. If you wish to embed an entire
 ///   file, simply specify only a `source_path` with no other arguments and the entire file
 ///   will be embedded as a doc example. If the path cannot be read for whatever reason, a
@@ -477,12 +917,280 @@ fn export_internal(
 ///   crate's root directory. While embedding files from a parent directory of the current
 ///   crate may work locally, this will fail when you go to deploy to `crates.io` and/or
 ///   `docs.rs`, so you should not use `../` or similar means unless you plan to never deploy
-///   to these services.
+///   to these services. The `source_path` may also be a glob pattern such as
+///   `"examples/tutorial_*.rs"`, in which case every matching file is embedded, one after
+///   another, in sorted order. If no files match the pattern, a compile error will be issued.
+///   Several comma-separated `source_path`s can also be specified in brackets, such as
+///   `["src/a.rs", "src/b.rs"]`, for examples that span several files, such as a trait in one
+///   file and its implementation in another. Each file is searched in turn, in the order
+///   given, and the matches from all of them are embedded one after another. It is fine for
+///   some of the files not to contain a match, but a compile error is issued if none of them
+///   do.
 /// - `item_ident`: (optional) can be specified after `source_path`, preceded by a comma. This
 ///   should match the export name you used to [`#[docify::export(..)]`](`macro@export`) the
 ///   item, or, if no export name was specified, this should match the inherent ident/name of
 ///   the item. If the item cannot be found, a compile error will be issued. As mentioned
 ///   above, if no `item_ident` is specified, the entire file will be embedded as an example.
+///   Items named after a keyword are specified using raw identifiers, such as `r#type`, and
+///   as in rust, `r#foo` and `foo` refer to the same item.
+///   Instead of an ident, a path of the form `MyType::my_method` can also be specified to embed
+///   a single method from an `impl` block for `MyType` (trait impls included), and likewise
+///   `MyType::MY_CONST` or `MyType::MyAssocType` for associated consts and types. Such members
+///   do not need to be marked with [`#[docify::export]`](`macro@export`). A path of the form
+///   `MyEnum::MyVariant` likewise embeds a single variant of `MyEnum`, shown inside an elided
+///   `enum MyEnum { .. }`. Similarly, a path such
+///   as `client::run` embeds only the exported items named `run` that are declared directly
+///   inside `mod client` (relative to the root of `source_path`), which is useful when several
+///   modules export items with the same name.
+/// - `line_range`: (optional) can be specified in place of `item_ident` to embed a specific
+///   range of lines from `source_path`, such as `10..25` or `10..=25`. Line numbers are
+///   1-based, and the range follows the usual rust semantics, so `10..25` excludes line 25
+///   while `10..=25` includes it. Line ranges are purely textual, so the file does not need to
+///   contain valid Rust source code in this case. If the range is empty or extends beyond the
+///   end of the file, a compile error will be issued.
+/// - `[foo, bar, baz]`: (optional) several comma-separated `item_ident`s (or `line_range`s)
+///   can be specified in brackets in place of a single one, to embed each of them in turn, in
+///   the order given, as separate examples. The options below apply to all of them.
+///
+/// ### Options
+/// Any number of comma-separated options can be specified after the arguments above. Options
+/// are either flags, like `unique`, or key-value pairs, like `key = "value"`. Since flags are
+/// also plain idents, an item whose name collides with a flag must be referred to by its raw
+/// identifier, i.e. `docify::embed!("src/lib.rs", r#unique)`, as otherwise the whole file is
+/// embedded with that flag. A compile error is issued if a flag is the first argument after the
+/// `source_path` and the file exports an item of the same name. Each option can be specified at
+/// most once.
+/// - `unique`: issues a compile error if more than one item matches `item_ident`, listing the
+///   line of each match. Useful for catching copy-paste mistakes in export names.
+/// - `workspace_relative`: resolves `source_path` relative to the root of the cargo workspace
+///   containing the current crate instead of the current crate root. Note that, like `../`
+///   paths, this will not work once the crate is published on its own.
+/// - `path_env = "OUT_DIR"`: resolves `source_path` relative to the directory in the specified
+///   environment variable instead, such as to embed code generated by a build script, i.e.
+///   `docify::embed!("gen.rs", my_item, path_env = "OUT_DIR")`. A compile error is issued if the
+///   variable is not set. Cannot be combined with `workspace_relative`.
+/// - `show_source`: appends a comment such as `// from src/foo.rs:42` to the end of each
+///   example, indicating the file (relative to the crate root) and line where the embedded
+///   code can be found.
+/// - `dedent`: removes the leading indentation shared by all non-blank lines of the embedded
+///   code. Embedded items are always dedented, so this is mainly useful for line range and
+///   whole-file embeds.
+/// - `signature_only`: embeds only the signature of the matching function (including any
+///   attributes, generics, and `where` clauses) followed by a `;`, omitting its body. This is
+///   useful for API overviews. Functions, methods, and trait methods with a default body are
+///   supported, and a compile error is issued for any other item.
+/// - `signatures_only`: embeds the matching trait definition with the default body of each
+///   of its methods replaced by a `;`, leaving only the method signatures. This is useful for
+///   documenting the contract of a trait. A compile error is issued for any other item.
+/// - `eval_const`: appends the value of the matching `const` or `static` item as a comment,
+///   i.e. `const MAX: usize = 1 << 20; // = 1048576`. Only integer literals combined with
+///   arithmetic, bitwise, and shift operators can be evaluated, and a compile error is issued
+///   for anything else.
+/// - `value_only`: embeds only the initializer expression of the matching `const` or `static`
+///   item or `let` statement, leaving out the `static NAME: Type =` declaration. Useful for
+///   documenting large literal configuration values. A compile error is issued for any other
+///   item.
+/// - `contents_only`: embeds only the contents of the matching module, leaving out the
+///   `mod my_module {` and `}` lines, and dedenting the contents accordingly. Useful when the
+///   module is only there to group the items of an example. This is the same as exporting the
+///   module with [`#[docify::export_content]`](`macro@export_content`), so it works for
+///   functions, `impl` blocks, and traits too, and a compile error is issued for any other
+///   item.
+/// - `respect_cfg`: skips items (including the contents of modules and `impl` blocks) with a
+///   `#[cfg(..)]` attribute that is inactive, so only code that is enabled for the current
+///   build is embedded. Only `feature = ".."` predicates, combined using `all(..)`, `any(..)`
+///   and `not(..)`, are evaluated; items with any other predicate are always embedded. Enabled
+///   features are read from the `CARGO_FEATURE_*` environment variables, which cargo only sets
+///   for build scripts, so your `build.rs` must forward them to the crate being compiled:
+///   ```ignore
+///   for (var, value) in std::env::vars().filter(|(var, _)| var.starts_with("CARGO_FEATURE_")) {
+///       println!("cargo:rustc-env={}={}", var, value);
+///   }
+///   ```
+/// - `marker = "foo"`: instead of an `item_ident` or `line_range`, embeds the lines between a
+///   line containing `docify:begin-foo` and a line containing `docify:end-foo`, such as
+///   `// docify:begin-foo` or `<!-- docify:begin-foo -->`. This is purely textual, so it works
+///   for fragments, templates, and non-rust files alike. If there are several such regions,
+///   each is embedded one after another, and a compile error is issued if there are none or a
+///   marker is missing its counterpart.
+/// - `region = "payload"`: instead of an `item_ident` or `line_range`, embeds the lines between
+///   a `// docify:region-start payload` comment and the matching `// docify:region-end`
+///   comment, with their shared leading indentation removed, such as a few match arms or an
+///   expression deep inside a function. Regions can be nested, in which case each
+///   `docify:region-end` ends the innermost open region, and the marker comments of nested
+///   regions are left out of the embedded code. Like `marker`, this is purely textual. If there
+///   are several regions with the same name, each is embedded one after another, and a compile
+///   error is issued if there are none, if a region overlaps another region with the same name,
+///   or if a marker is missing its counterpart.
+/// - `from_marker = "// docify:start"`: instead of the entire file, embeds everything after
+///   the first line containing the specified text, which is useful for leaving out a license
+///   header, imports, or other boilerplate at the top of an example file. Like `marker`, this
+///   is purely textual, and a compile error is issued if no line contains the text.
+/// - `category = "networking"`: instead of an `item_ident` or `line_range`, embeds every item
+///   exported with the specified category, i.e. `#[docify::export(category = "networking")]`,
+///   one after another in the order they appear in `source_path`. A category can be combined
+///   with export names, as in `#[docify::export(fetch, category = "networking")]`. A compile
+///   error is issued if there are no such items.
+/// - `regex = "^handle_"`: instead of an `item_ident` or `line_range`, embeds every item
+///   exported under a name matching the specified regular expression (see the
+///   [regex crate](https://docs.rs/regex) for its syntax), one after another in the order they
+///   appear in `source_path`, such as a family of functions named `handle_get`, `handle_post`,
+///   and so on. The regex can match anywhere in the name unless anchored with `^` and `$`. A
+///   compile error is issued if there are no such items.
+/// - `lines = 5..15`: embeds only the specified lines of each item matching `item_ident`,
+///   counting from the first line of the item, for when only part of a long function is of
+///   interest. Follows the same rules as a `line_range`, so a compile error is issued if the
+///   range extends beyond the end of the item. The shared leading indentation of the lines is
+///   removed. Can only be combined with an `item_ident`.
+/// - `sort = "name"`: embeds the items matching `item_ident` (or `category` or `regex`) in
+///   alphabetical order of their inherent names rather than in the order they appear in
+///   `source_path`, for when the order of the source file doesn't suit the docs. `impl` blocks
+///   are sorted by their derived names, such as `Foo_impl` (see
+///   [`#[docify::export]`](`macro@export`)), and items without a name come first.
+///   `sort = "reverse"` embeds them in reverse order instead, while the default is
+///   `sort = "source"`.
+/// - `placeholder = "/* snip */"`: uses the specified text instead of `// ...` in place of
+///   elided code, i.e. regions hidden with `// docify:hide-start` (see below) and the other
+///   variants of an enum when embedding a single variant. Must be a single line.
+/// - `fence = "ignore,my-tag"`: uses the specified string after the opening "```" of each
+///   example instead of the default (`rust,ignore` for
+///   [`docify::embed!(..)`](`macro@embed`)), such as to pass extra attributes like
+///   `edition2021` to rustdoc, or custom tags to tools such as mdBook. Takes precedence over
+///   `lang`, and cannot contain newlines or backticks. Specifying `fence = "ignore"` restores
+///   the bare "```ignore" fences emitted by docify 0.2.
+/// - `edition = "2021"`: appends the corresponding `edition2021` attribute to the info string
+///   after the opening "```" of each example, i.e. "```rust,ignore,edition2021", so that rustdoc
+///   compiles examples run via [`docify::embed_run!(..)`](`macro@embed_run`) with that edition
+///   rather than the edition of the crate being documented. Useful for examples that use
+///   edition-specific syntax. Must be one of `"2015"`, `"2018"`, `"2021"`, or `"2024"`, and
+///   cannot be combined with `lang`.
+/// - `max_width = 80`: wraps comment lines (including doc comments) of the embedded code that
+///   are longer than the specified number of characters at word boundaries, for docs that are
+///   rendered in a narrow column. Since embedded code is excerpted verbatim rather than being
+///   re-formatted, long lines of code are left as-is, so this is only a best-effort limit.
+/// - `indent = 4`: prefixes every line of the output, including the fences and any captions,
+///   with the specified number of spaces (less than 64), so that an example can be placed
+///   inside a nested markdown list item, such as in a file compiled by
+///   [`compile_markdown!`](`macro@compile_markdown`).
+/// - `title = "example.rs"`: labels each example with the specified title, such as a file name,
+///   to help readers tell several embedded examples apart. By default this is done with a
+///   `// example.rs` comment on the first line of the example. Cannot contain newlines or
+///   backticks.
+/// - `title_style = "fence"`: places the `title` after the opening "```" of each example
+///   instead, i.e. "```rust,ignore,title=example.rs", for renderers such as mdBook that support
+///   titled codeblocks. Defaults to `"comment"`.
+/// - `highlight = "client"`: emphasizes each line of the example that contains the identifier
+///   `client`, by rendering the example as an HTML `<pre>` block with those lines wrapped in
+///   `<mark>`, since markdown codeblocks can't contain markup. The example loses rustdoc's
+///   syntax highlighting in exchange, so this is best kept to the examples of tutorials that
+///   walk through one part at a time. A compile error is issued if no line contains the
+///   identifier. Only supported by `docify::embed!(..)`, and cannot be combined with `raw`,
+///   `fence`, `edition`, `indent`, or `title_style = "fence"`.
+/// - `strip_docs`: removes doc comments (`///` and `//!`) and `#[doc = ".."]` attributes from
+///   the embedded code, such as those of nested items, to keep examples terse. By default
+///   these are kept, though doc comments on the lines before an embedded item are never
+///   considered part of it.
+/// - `strip_attrs = "tokio::main,serde"`: removes attributes whose path is one of the
+///   specified comma-separated paths, or is nested inside one of them, from the embedded code.
+///   For example `"serde"` removes both `#[serde(rename = "id")]` and `#[serde::skip]`, while
+///   `#[derive(Serialize)]` is kept. Useful for leaving out framework attributes that would
+///   distract readers from the code being shown. Attributes are matched by how they are
+///   written, so `"tokio::main"` doesn't match `#[main]`, even if `tokio::main` was imported.
+/// - `no_attrs`: removes every outer attribute, such as `#[inline]` or `#[derive(..)]`, from the
+///   embedded code (including those of nested items and fields), for when the teaching point
+///   is the code itself rather than the attributes it is annotated with. Doc comments and
+///   `#[doc = ".."]` attributes are governed by `strip_docs` instead, and inner attributes
+///   such as `#![allow(..)]` are kept.
+/// - `strip_test`: removes test attributes from the embedded code, i.e. `#[test]`,
+///   `#[should_panic]`, `#[ignore]`, and attributes ending in `::test` such as `#[tokio::test]`
+///   or `#[async_std::test]`, so that a test run by CI can double as a clean example.
+/// - `keep_doc_hidden`: keeps `#[doc(hidden)]` attributes in embedded items. By default these
+///   are removed, since an item that is shown in the docs as an example isn't hidden from
+///   them. Line ranges, markers, and whole-file embeds are left as-is.
+/// - `line_numbers`: prefixes each line of the embedded code with a right-aligned `N | ` gutter,
+///   numbering the lines of the example starting from 1. Since this makes the example
+///   uncompilable, this option cannot be used with
+///   [`docify::embed_run!(..)`](`macro@embed_run`) or
+///   [`docify::embed_no_run!(..)`](`macro@embed_no_run`).
+/// - `single_fence`: when several items match `item_ident` (or several files match a glob
+///   `source_path`), embeds them one after another, separated by a blank line, in a single
+///   codeblock rather than one codeblock each. Useful for showing a struct, its `impl`, and a
+///   helper function that share an export name as one contiguous example.
+/// - `raw`: returns the embedded code without wrapping it in a codeblock, for when you want
+///   full control over how it is presented, such as inside a codeblock or table of your own.
+///   Several matches are separated by a blank line. Since this leaves nothing for rustdoc to
+///   compile, this option cannot be used with [`docify::embed_run!(..)`](`macro@embed_run`) or
+///   [`docify::embed_no_run!(..)`](`macro@embed_no_run`), nor combined with the options that
+///   apply to the codeblock (`fence`, `edition`, `lang`, `title`, `title_style`, and
+///   `numbered`).
+/// - `show_desc`: captions each example with the description of the embedded item, as given by
+///   `#[docify::export(my_name, desc = "Sets up the client")]`, so that descriptions of examples
+///   can live alongside their code. The description is shown as plain text, escaped as needed,
+///   above the codeblock. Examples of items without a description are left uncaptioned.
+/// - `numbered`: captions each example with its number, i.e. `**Step 1**`, `**Step 2**`, and so
+///   on, for step-by-step tutorials that embed several items (or files) at once. Numbering
+///   starts from 1 for each `docify::embed!(..)` call.
+/// - `emit_metadata`: emits an HTML comment before each example describing where it was
+///   embedded from, such as
+///   `<!-- docify: {"path":"src/foo.rs","item":"my_item","lines":[10,25]} -->`, for tools such
+///   as mdBook preprocessors that link examples back to their source. The `path` is relative
+///   to the crate root (or the URL of a remote file), `item` is `null` when no `item_ident` was
+///   specified, and `lines` is the range of lines (inclusive) the example was excerpted from.
+///   HTML comments aren't rendered by rustdoc, so this is invisible to readers.
+/// - `pub_only`: leaves the items of an embedded module that aren't visible outside of it, i.e.
+///   those without a `pub`, `pub(crate)`, or other `pub(..)` visibility, out of the embedded
+///   code, along with their doc comments, to document the public surface of a module from its
+///   source. Items of public submodules are filtered in the same way, while items that have no
+///   visibility of their own, such as `impl` blocks, are kept. Can only be used with inline
+///   modules.
+/// - `with_module_path`: wraps each embedded item in a skeleton of the modules it is nested in
+///   within `source_path`, such as `mod outer { mod inner { .. } }`, so readers can see where
+///   it lives. Has no effect on line ranges, markers, and whole-file embeds.
+/// - `with_imports`: prepends the top-level `use` items of `source_path` that are referred to
+///   by each embedded item, so examples run via [`docify::embed_run!(..)`](`macro@embed_run`)
+///   are more likely to compile. Glob imports such as `use super::*;` are always included. Has
+///   no effect on line ranges, markers, and whole-file embeds.
+/// - `with_preamble`: includes the comment lines (`//` or `/* .. */`) immediately preceding each
+///   embedded item, such as a comment describing the example, which are otherwise left out.
+///   The preamble ends at the first blank line or line of code above the item, though
+///   `#[docify::export]` attributes are skipped over. Has no effect on line ranges, markers,
+///   and whole-file embeds.
+/// - `wrap_main`: wraps embedded code that consists of loose statements or an expression, such
+///   as an exported `let` statement, in a `fn main() { .. }`, so that readers see a complete,
+///   runnable program. Code that already consists of complete items, such as a function
+///   (including `fn main` itself), is left as-is.
+/// - `no_check`: skips checking that examples embedded with
+///   [`docify::embed_run!(..)`](`macro@embed_run`) or
+///   [`docify::embed_no_run!(..)`](`macro@embed_no_run`) parse as valid rust, for fragments
+///   that intentionally don't. See [`docify::embed_run!(..)`](`macro@embed_run`).
+/// - `hash = ".."`: pins a hash of the embedded code, issuing a compile error if the code no
+///   longer matches, such as when an example has changed since the surrounding docs were last
+///   reviewed. The error includes the new hash, so once the docs have been updated it can be
+///   copied into the `hash` option. Useful for curated tutorials, where the prose explaining an
+///   example can silently drift from the example itself.
+/// - `sha256 = ".."`: pins the SHA-256 checksum (hex-encoded) of a file embedded from a URL,
+///   issuing a compile error if the downloaded contents don't match. See below.
+/// - `lang = "toml"`: embeds a non-rust file, such as a `Cargo.toml` or a shell script, wrapped
+///   in a codeblock of the given language. The file is not parsed, so only whole files and line
+///   ranges can be embedded this way. Embedding an entire file that doesn't parse as rust
+///   without specifying `lang` is a compile error, so that arbitrary text never ends up in a
+///   rust codeblock; use `lang = "text"` for plain text files.
+///
+/// ### Remote Files
+/// When the `remote` feature of docify is enabled, the `source_path` may also be an `http://` or
+/// `https://` URL, such as a link to the raw contents of a file in another repository:
+/// ```ignore
+/// #[doc = docify::embed!(
+///     "https://raw.githubusercontent.com/org/repo/v1.0.0/examples/foo.rs",
+///     my_item,
+///     sha256 = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+/// )]
+/// ```
+/// Files are downloaded once and then cached in `OUT_DIR` if your crate has a build script.
+/// Otherwise they are cached in the system's temporary directory, but only reused if a `sha256`
+/// checksum is pinned, and are downloaded again on every build if not. A compile error is issued
+/// if the download fails or the file is larger than 10 MiB. Pinning a `sha256` checksum, along
+/// with linking to a tag or commit rather than a branch, keeps builds reproducible.
 ///
 /// All items in the `source_file` exist in the same global scope when they are exported for
 /// embedding. Special care must be taken with how you
@@ -491,7 +1199,8 @@ fn export_internal(
 /// If there multiple items in a file that resolve to the same `item_ident` (whether as an
 /// inherent ident name or as a manually specified `item_ident`), and you embed using this
 /// ident, all matching items will be embedded, one after another, listed in the order that
-/// they appear in the `source_file`.
+/// they appear in the `source_file`, unless the `unique` option is specified in which case
+/// a compile error will be issued instead.
 ///
 /// Here is an example of embedding an _entire_ source file as an example:
 /// ```ignore
@@ -500,6 +1209,13 @@ fn export_internal(
 /// struct DocumentedItem
 /// ```
 ///
+/// And here is an example of embedding only lines 10 through 25 of a file:
+/// ```ignore
+/// /// Here are the interesting parts of the config:
+/// #[doc = docify::embed!("examples/my_config.rs", 10..=25)]
+/// struct DocumentedItem;
+/// ```
+///
 /// You are also free to embed multiple examples in the same set of doc comments:
 /// ```ignore
 /// /// Example 1:
@@ -516,9 +1232,44 @@ fn export_internal(
 /// for whatever reason you _do_ want to also run an embedded example as a doc example, you can
 /// use [`docify::embed_run!(..)`](`macro@embed_run`) which removes the `ignore` tag from the
 /// generated example but otherwise functions exactly like `#[docify::embed!(..)]` in every
-/// way.
+/// way. Examples are also tagged as `rust`, i.e. "```rust,ignore", so that renderers other
+/// than rustdoc apply syntax highlighting, which can be overridden with the `fence` option.
+///
+/// Embedded items are excerpted directly from the text of `source_path` rather than being
+/// re-formatted, so intentional alignment, blank lines, and comments (including ASCII-art
+/// comments) are preserved exactly as they appear in the original source. The only change
+/// made is that the shared leading indentation of the excerpt is removed, so items nested
+/// inside modules are not indented in the resulting example. Since no formatter is ever run
+/// over embedded code, code that `rustfmt` would lay out differently, such as long match arms
+/// or unusual attribute placement, is embedded exactly as written as well.
+///
+/// Comments inside an embedded item are always kept, as is a comment trailing the last line
+/// of the item (i.e. `} // end of example`). Comments appearing on the lines _before_ the item
+/// are not considered part of it.
+///
+/// Items that aren't relevant to readers of a whole-file embed, such as a helper `main()`
+/// function or a test module, can be marked with [`#[docify::hide]`](`macro@hide`) to leave
+/// them out.
 ///
-/// Output should match `rustfmt` output exactly.
+/// Parts of an example that aren't relevant to readers, such as lengthy setup code, can be
+/// hidden by surrounding them with `// docify:hide-start` and `// docify:hide-end` comments,
+/// each on its own line. Everything from the first comment to the second is replaced with a
+/// single `// ...` comment (or the `placeholder` option, if specified) in the embedded example:
+/// ```ignore
+/// #[docify::export]
+/// fn long_example() {
+///     let client = Client::new();
+///     // docify:hide-start
+///     client.configure_retries(3);
+///     client.configure_timeout(30);
+///     // docify:hide-end
+///     client.connect();
+/// }
+/// ```
+///
+/// Similarly, lines that must not appear in published docs, such as placeholder API keys or
+/// internal paths, can be surrounded with `// docify:redact-start` and `// docify:redact-end`
+/// comments, in which case they are replaced with a single `/* redacted */` comment.
 #[proc_macro]
 pub fn embed(tokens: TokenStream) -> TokenStream {
     match embed_internal(tokens, MarkdownLanguage::Ignore) {
@@ -532,6 +1283,12 @@ pub fn embed(tokens: TokenStream) -> TokenStream {
 ///
 /// Other than this fact all of the usual docs and syntax and behaviors for
 /// [`docify::embed!(..)`](`macro@embed`) also apply to this macro.
+///
+/// So that a broken example is caught when the docs are built rather than later on by
+/// `cargo test`, a compile error is issued if an embedded item or file doesn't parse as either a
+/// rust source file or a sequence of statements. This check can be skipped with the `no_check`
+/// option, and also applies to [`docify::embed_no_run!(..)`](`macro@embed_no_run`). Line ranges
+/// and markers (including `from_marker`) are never checked, since they are often fragments.
 #[proc_macro]
 pub fn embed_run(tokens: TokenStream) -> TokenStream {
     match embed_internal(tokens, MarkdownLanguage::Blank) {
@@ -540,72 +1297,1077 @@ pub fn embed_run(tokens: TokenStream) -> TokenStream {
     }
 }
 
-/// Used to parse args for `docify::embed!(..)`
-#[derive(Parse)]
-struct EmbedArgs {
-    file_path: LitStr,
-    #[prefix(Option<Token![,]> as comma)]
-    #[parse_if(comma.is_some())]
-    item_ident: Option<Ident>,
+/// Exactly like [`docify::embed!(..)`](`macro@embed`) in every way _except_ the generated
+/// examples are marked `no_run` rather than `ignore`, so they are compiled as part of doc tests
+/// but never executed.
+///
+/// This is useful for examples that must stay valid but can't be run in a doc test, such as
+/// ones that access the network or never terminate. Other than this fact all of the usual docs
+/// and syntax and behaviors for [`docify::embed!(..)`](`macro@embed`) also apply to this macro.
+#[proc_macro]
+pub fn embed_no_run(tokens: TokenStream) -> TokenStream {
+    match embed_internal(tokens, MarkdownLanguage::NoRun) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
 
-impl ToTokens for EmbedArgs {
-    fn to_tokens(&self, tokens: &mut TokenStream2) {
-        tokens.extend(self.file_path.to_token_stream());
-        let Some(item_ident) = &self.item_ident else {
-            return;
-        };
-        tokens.extend(quote!(,));
-        tokens.extend(item_ident.to_token_stream());
+/// Exactly like [`docify::embed!(..)`](`macro@embed`) _except_ that if the example can't be
+/// embedded, such as because the source file is missing or the item can't be found, a
+/// placeholder example is embedded in its place rather than failing the build:
+/// ```ignore
+/// #[doc = docify::try_embed!("examples/samples.rs", renamed_example)]
+/// ```
+/// would then expand to the following, while the original error is printed to standard output
+/// during compilation:
+/// ````markdown
+/// ```rust,ignore
+/// // [docify: could not embed 'renamed_example' from 'examples/samples.rs']
+/// ```
+/// ````
+///
+/// Note that this message is not a compiler warning: it isn't affected by `#![deny(warnings)]`,
+/// isn't shown by editors such as rust-analyzer, and is only printed when the crate is actually
+/// rebuilt, not when cargo reuses a cached build.
+///
+/// This lets docs keep building during refactors that move examples around, at the cost of
+/// the broken example only being noticed in the build output or the rendered docs, so
+/// [`docify::embed!(..)`](`macro@embed`) should be preferred otherwise. Arguments that don't
+/// parse at all are still compile errors. Other than this fact all of the usual docs and
+/// syntax and behaviors for [`docify::embed!(..)`](`macro@embed`) also apply to this macro.
+#[proc_macro]
+pub fn try_embed(tokens: TokenStream) -> TokenStream {
+    match try_embed_internal(tokens) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
     }
 }
 
-mod keywords {
-    use syn::custom_keyword;
-
-    custom_keyword!(docify);
-    custom_keyword!(embed);
+/// Embeds the differences between the specified item in two source files, such as the old and
+/// new versions of an example, as a rust doc example in the form of a diff.
+///
+/// Should be used in a `#[doc = ...]` statement, like the following:
+///
+/// ```ignore
+/// /// Here is how to migrate to the new API:
+/// #[doc = docify::embed_diff!("examples/v1.rs", "examples/v2.rs", setup)]
+/// struct MigrationGuide;
+/// ```
+///
+/// Which will expand to a "```diff" codeblock containing every line of the `setup` item,
+/// prefixed with `-` if it only appears in `examples/v1.rs`, `+` if it only appears in
+/// `examples/v2.rs`, or a space if it appears in both. If the item is only found in one of the
+/// files, it is shown as entirely removed or added, while a compile error is issued if it
+/// can't be found in either.
+///
+/// Other than taking two source paths, the arguments and options are the same as those of
+/// [`docify::embed!(..)`](`macro@embed`), and apply to both files. Like
+/// [`docify::embed!(..)`](`macro@embed`), the item can be omitted to compare entire files.
+#[proc_macro]
+pub fn embed_diff(tokens: TokenStream) -> TokenStream {
+    match embed_diff_internal(tokens) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
 
-/// Used to parse a full `docify::embed!(..)` call, as seen in markdown documents and other
-/// embedded settings
-#[derive(Parse)]
-struct EmbedCommentCall {
-    #[prefix(keywords::docify)]
-    #[prefix(Token![::])]
-    #[prefix(keywords::embed)]
-    #[prefix(Token![!])]
-    #[paren]
-    _paren: Paren,
-    #[inside(_paren)]
-    args: EmbedArgs,
-    _semi: Option<Token![;]>,
+/// Embeds only the doc comments of the specified item, rather than its code, as plain markdown.
+///
+/// Should be used in a `#[doc = ...]` statement, like the following:
+///
+/// ```ignore
+/// /// Here is what the `setup` example does:
+/// #[doc = docify::embed_docs!("examples/samples.rs", setup)]
+/// struct SetupDocs;
+/// ```
+///
+/// Which will expand to the text of the doc comments attached to the `setup` item, without
+/// the leading `///` and without a codeblock, so the same explanation can be shared between an
+/// example and the docs that embed it. Doc comments inside the item are not included.
+///
+/// The arguments are the same as those of [`docify::embed!(..)`](`macro@embed`), except an
+/// `item_ident` is required. Only the `respect_cfg`, `unique`, and `workspace_relative`
+/// options have any effect. A compile error is issued if the item has no doc comments.
+#[proc_macro]
+pub fn embed_docs(tokens: TokenStream) -> TokenStream {
+    match embed_docs_internal(tokens) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
 
-/// This corresponds with the string immediately following the "```" in codeblocks. Blank means
-/// no language is specified. Ignore will cause the example not to run in rust docs.
-#[derive(Copy, Clone, Eq, PartialEq)]
-enum MarkdownLanguage {
-    Ignore,
-    Rust,
-    Blank,
+/// Embeds every `.rs` file in the specified directory, one after another in sorted order, each
+/// as its own rust doc example.
+///
+/// Should be used in a `#[doc = ...]` statement, like the following:
+///
+/// ```ignore
+/// /// Every example in the project:
+/// #[doc = docify::embed_dir!("examples/", recursive)]
+/// struct Examples;
+/// ```
+///
+/// By default only the files directly inside the directory are embedded, while with the
+/// `recursive` flag the files in all of its subdirectories are embedded too. This is equivalent
+/// to a glob `source_path` such as `"examples/**/*.rs"`, so all of the other options of
+/// [`docify::embed!(..)`](`macro@embed`) can be used as well, and a compile error is issued if
+/// there are no such files. So that it can be verified that nothing was missed or included by
+/// accident, the path of each embedded file is printed as a note during compilation.
+#[proc_macro]
+pub fn embed_dir(tokens: TokenStream) -> TokenStream {
+    match embed_dir_internal(tokens) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
 
-/// Converts a source string to a codeblocks wrapped example
-fn into_example(st: &str, lang: MarkdownLanguage) -> String {
-    let mut lines: Vec<String> = Vec::new();
-    match lang {
-        MarkdownLanguage::Ignore => lines.push(String::from("```ignore")),
-        MarkdownLanguage::Rust => lines.push(String::from("```rust")),
-        MarkdownLanguage::Blank => lines.push(String::from("```")),
+/// Expands to an array of the names of every item exported from the specified source file, in
+/// the order they appear, as `&str`s.
+///
+/// This is useful for tooling and tests that want to enumerate the examples in a file, such as
+/// checking that each of them is documented somewhere:
+///
+/// ```ignore
+/// const EXAMPLES: &[&str] = &docify::list_exports!("examples/samples.rs");
+/// ```
+///
+/// Every name an item is exported under via [`#[docify::export(..)]`](`macro@export`),
+/// [`#[docify::export_content(..)]`](`macro@export_content`), or
+/// [`docify::export_stmt!(..)`](`macro@export_stmt`) is included, so an item exported under
+/// several aliases appears once per alias, and a name exported more than once appears once per
+/// export. Like [`docify::embed!(..)`](`macro@embed`), the path is relative to the root
+/// of the crate being compiled, and a compile error is issued if the file can't be read or
+/// parsed, or contains malformed export attributes.
+#[proc_macro]
+pub fn list_exports(tokens: TokenStream) -> TokenStream {
+    match list_exports_internal(tokens) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Issues a compile error listing every item exported from a `.rs` file in the specified
+/// source directory that is never embedded by a file in the specified docs directory, so that
+/// orphaned examples are caught as soon as the docs that used them are removed.
+///
+/// Should be used at the top level of a module, like the following:
+///
+/// ```ignore
+/// docify::assert_all_embedded!("examples", "src");
+/// ```
+///
+/// Both directories are relative to the root of the crate being compiled, and are searched
+/// recursively. Every `docify::embed!(..)`, `docify::embed_run!(..)`,
+/// `docify::embed_no_run!(..)`, `docify::embed_docs!(..)`, and `docify::embed_diff!(..)` call
+/// found in a `.rs` or `.md` file in the docs directory (including those in the HTML comments
+/// of markdown files processed by [`compile_markdown!`](`macro@compile_markdown`)) counts as
+/// embedding the items it names, regardless of which file it names them in. A call that
+/// doesn't name an item, such as one embedding a whole file, line range, or category, counts
+/// as embedding every export in the files it refers to. Since this is only checked when the
+/// crate containing the call is compiled, editing the docs alone won't re-run the check.
+///
+/// If every export is embedded, this expands to nothing.
+#[proc_macro]
+pub fn assert_all_embedded(tokens: TokenStream) -> TokenStream {
+    match assert_all_embedded_internal(tokens) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Used to parse args for `docify::assert_all_embedded!(..)`
+#[derive(Parse)]
+struct AssertAllEmbeddedArgs {
+    src_dir: LitStr,
+    _comma: Token![,],
+    doc_dir: LitStr,
+}
+
+/// Used to parse args for `docify::embed_dir!(..)`
+struct EmbedDirArgs {
+    dir_path: LitStr,
+    options: Vec<EmbedOption>,
+}
+
+impl Parse for EmbedDirArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let dir_path = input.parse::<LitStr>()?;
+        let mut options = Vec::new();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            options.push(input.parse::<EmbedOption>()?);
+        }
+        Ok(EmbedDirArgs { dir_path, options })
+    }
+}
+
+/// Used to parse args for `docify::embed_diff!(..)`
+#[derive(Parse)]
+struct EmbedDiffArgs {
+    old_file_path: LitStr,
+    _comma: Token![,],
+    new_args: EmbedArgs,
+}
+
+/// Used to parse args for `docify::embed!(..)`
+#[derive(Clone)]
+struct EmbedArgs {
+    file_path: LitStr,
+    /// Several source paths specified as `["a.rs", "b.rs"]`, each searched in turn, in which
+    /// case `file_path` is the first of them.
+    file_paths: Vec<LitStr>,
+    target: Option<EmbedTarget>,
+    /// Several targets specified as `[foo, bar]`, each embedded in turn, in which case `target`
+    /// is `None`.
+    targets: Vec<EmbedTarget>,
+    options: Vec<EmbedOption>,
+}
+
+impl Parse for EmbedArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut file_paths = Vec::new();
+        let file_path = match input.peek(Bracket) {
+            true => {
+                let content;
+                let brackets = bracketed!(content in input);
+                file_paths = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?
+                    .into_iter()
+                    .collect();
+                match file_paths.first() {
+                    Some(file_path) => file_path.clone(),
+                    None => {
+                        return Err(Error::new(
+                            brackets.span.join(),
+                            "Expected at least one `source_path` inside the brackets.",
+                        ))
+                    }
+                }
+            }
+            false if input.peek(Ident::peek_any) || input.peek(LitInt) => {
+                let target = input.parse::<EmbedTarget>()?;
+                let swapped_path = input
+                    .parse::<Token![,]>()
+                    .and_then(|_| input.parse::<LitStr>());
+                let message = match swapped_path {
+                    Ok(path) => format!(
+                        "The first argument must be a string literal path, did you swap the \
+                        path and item name? i.e. `docify::embed!({}, {})`",
+                        path.token(),
+                        target.to_token_stream()
+                    ),
+                    Err(_) => String::from(
+                        "The first argument must be a string literal path to the source file.",
+                    ),
+                };
+                return Err(Error::new_spanned(&target, message));
+            }
+            false => input.parse::<LitStr>()?,
+        };
+        let mut target = None;
+        let mut targets = Vec::new();
+        let mut options = Vec::new();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let first = target.is_none() && targets.is_empty() && options.is_empty();
+            if first && input.peek(Bracket) {
+                let content;
+                let brackets = bracketed!(content in input);
+                targets = Punctuated::<EmbedTarget, Token![,]>::parse_terminated(&content)?
+                    .into_iter()
+                    .collect();
+                if targets.is_empty() {
+                    return Err(Error::new(
+                        brackets.span.join(),
+                        "Expected at least one `item_ident` or `line_range` inside the brackets.",
+                    ));
+                }
+            } else if first && !EmbedOption::peek(input) {
+                target = Some(input.parse::<EmbedTarget>()?);
+            } else {
+                options.push(input.parse::<EmbedOption>()?);
+            }
+        }
+        Ok(EmbedArgs {
+            file_path,
+            file_paths,
+            target,
+            targets,
+            options,
+        })
+    }
+}
+
+impl ToTokens for EmbedArgs {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self.file_paths.is_empty() {
+            true => tokens.extend(self.file_path.to_token_stream()),
+            false => {
+                let file_paths = &self.file_paths;
+                tokens.extend(quote!([#(#file_paths),*]));
+            }
+        }
+        if let Some(target) = &self.target {
+            tokens.extend(quote!(,));
+            tokens.extend(target.to_token_stream());
+        }
+        if !self.targets.is_empty() {
+            let targets = &self.targets;
+            tokens.extend(quote!(, [#(#targets),*]));
+        }
+        for option in &self.options {
+            tokens.extend(quote!(,));
+            tokens.extend(option.to_token_stream());
+        }
+    }
+}
+
+/// Names of the flag-style options supported by `docify::embed!(..)`. Because an `item_ident`
+/// is also just an ident, these names cannot be used as an `item_ident`.
+const EMBED_FLAGS: &[&str] = &[
+    "unique",
+    "workspace_relative",
+    "show_source",
+    "dedent",
+    "signature_only",
+    "signatures_only",
+    "respect_cfg",
+    "strip_docs",
+    "keep_doc_hidden",
+    "no_attrs",
+    "strip_test",
+    "pub_only",
+    "line_numbers",
+    "single_fence",
+    "raw",
+    "emit_metadata",
+    "show_desc",
+    "numbered",
+    "with_module_path",
+    "with_imports",
+    "with_preamble",
+    "eval_const",
+    "value_only",
+    "contents_only",
+    "wrap_main",
+    "no_check",
+];
+
+/// A single option passed to `docify::embed!(..)` after the (optional) `item_ident`, either a
+/// flag such as `unique` or a key-value pair such as `key = "value"`.
+#[derive(Clone)]
+struct EmbedOption {
+    name: Ident,
+    value: Option<EmbedOptionValue>,
+}
+
+/// The value of a key-value [`EmbedOption`], either a literal or a range of lines such as
+/// `lines = 5..15`.
+#[derive(Clone)]
+enum EmbedOptionValue {
+    Lit(Lit),
+    Lines(LineRange),
+}
+
+impl ToTokens for EmbedOptionValue {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self {
+            EmbedOptionValue::Lit(lit) => lit.to_tokens(tokens),
+            EmbedOptionValue::Lines(range) => range.to_tokens(tokens),
+        }
+    }
+}
+
+impl EmbedOption {
+    /// Returns `true` if the next tokens in `input` should be parsed as an [`EmbedOption`]
+    /// rather than as an [`EmbedTarget`].
+    fn peek(input: ParseStream) -> bool {
+        let fork = input.fork();
+        let Ok(name) = fork.call(Ident::parse_any) else {
+            return false;
+        };
+        fork.peek(Token![=]) || EMBED_FLAGS.contains(&name.to_string().as_str())
+    }
+
+    /// Ensures this option was specified as a flag, i.e. without a value.
+    fn flag(&self) -> Result<bool> {
+        match &self.value {
+            Some(value) => Err(Error::new(
+                value.span(),
+                format!("The `{}` option does not take a value.", self.name),
+            )),
+            None => Ok(true),
+        }
+    }
+
+    /// Ensures this option was specified with a string literal value, returning that value.
+    fn string(&self) -> Result<String> {
+        match &self.value {
+            Some(EmbedOptionValue::Lit(Lit::Str(value))) => Ok(value.value()),
+            Some(value) => Err(Error::new(
+                value.span(),
+                format!("The `{}` option expects a string literal.", self.name),
+            )),
+            None => Err(Error::new(
+                self.name.span(),
+                format!(
+                    "The `{}` option requires a value, like `{} = \"..\"`.",
+                    self.name, self.name
+                ),
+            )),
+        }
+    }
+
+    /// Ensures this option was specified with a non-zero integer literal value, returning that
+    /// value.
+    fn positive_int(&self) -> Result<usize> {
+        match &self.value {
+            Some(EmbedOptionValue::Lit(Lit::Int(value))) => match value.base10_parse::<usize>()? {
+                0 => Err(Error::new(
+                    value.span(),
+                    format!("The `{}` option must be greater than zero.", self.name),
+                )),
+                value => Ok(value),
+            },
+            Some(value) => Err(Error::new(
+                value.span(),
+                format!("The `{}` option expects an integer literal.", self.name),
+            )),
+            None => Err(Error::new(
+                self.name.span(),
+                format!(
+                    "The `{}` option requires a value, like `{} = 80`.",
+                    self.name, self.name
+                ),
+            )),
+        }
+    }
+
+    /// Ensures this option was specified with a range of lines, such as `5..15`, returning that
+    /// range.
+    fn line_range(&self) -> Result<LineRange> {
+        match &self.value {
+            Some(EmbedOptionValue::Lines(range)) => Ok(range.clone()),
+            Some(value) => Err(Error::new_spanned(
+                value,
+                format!("The `{}` option expects a range of lines.", self.name),
+            )),
+            None => Err(Error::new(
+                self.name.span(),
+                format!(
+                    "The `{}` option requires a value, like `{} = 5..15`.",
+                    self.name, self.name
+                ),
+            )),
+        }
+    }
+}
+
+impl Parse for EmbedOption {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.call(Ident::parse_any)?;
+        let value = match input.parse::<Option<Token![=]>>()? {
+            Some(_) if input.peek(LitInt) && input.peek2(Token![..]) => {
+                Some(EmbedOptionValue::Lines(input.parse()?))
+            }
+            Some(_) => Some(EmbedOptionValue::Lit(input.parse()?)),
+            None => None,
+        };
+        Ok(EmbedOption { name, value })
+    }
+}
+
+impl ToTokens for EmbedOption {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        self.name.to_tokens(tokens);
+        if let Some(value) = &self.value {
+            tokens.extend(quote!(=));
+            value.to_tokens(tokens);
+        }
+    }
+}
+
+/// The resolved set of [`EmbedOption`]s passed to a `docify::embed!(..)` call.
+#[derive(Default)]
+struct EmbedOptions {
+    /// Issue a compile error if more than one item matches the `item_ident`.
+    unique: bool,
+    /// Resolve the `source_path` relative to the workspace root rather than the crate root.
+    workspace_relative: bool,
+    /// Resolve the `source_path` relative to the directory in this environment variable,
+    /// along with that directory.
+    path_env: Option<(String, PathBuf)>,
+    /// Append a `// from path:line` comment to each example.
+    show_source: bool,
+    /// Remove the shared leading indentation of line range and whole-file embeds.
+    dedent: bool,
+    /// Embed only the signatures of matching functions, omitting their bodies.
+    signature_only: bool,
+    /// Embed only the signatures of the methods of matching traits, omitting default bodies.
+    signatures_only: bool,
+    /// Skip items whose `#[cfg(feature = "..")]` is inactive.
+    respect_cfg: bool,
+    /// Remove doc comments and `#[doc = ".."]` attributes from embedded code.
+    strip_docs: bool,
+    /// Remove attributes with these paths (or paths nested inside them) from embedded code.
+    strip_attrs: Vec<String>,
+    /// Keep `#[doc(hidden)]` attributes in embedded items rather than removing them.
+    keep_doc_hidden: bool,
+    /// Remove every outer attribute other than doc comments from embedded code.
+    no_attrs: bool,
+    /// Remove test attributes such as `#[test]` and `#[tokio::test]` from embedded code.
+    strip_test: bool,
+    /// Leave the items of embedded modules that aren't `pub` out of the embedded code.
+    pub_only: bool,
+    /// Prefix each line of the embedded code with its line number.
+    line_numbers: bool,
+    /// Embed all matches in a single codeblock rather than one codeblock each.
+    single_fence: bool,
+    /// Return the embedded code as-is, without wrapping it in a codeblock.
+    raw: bool,
+    /// Emit an HTML comment describing where each example came from before its codeblock.
+    emit_metadata: bool,
+    /// Caption each example with the `desc = ".."` of the embedded item.
+    show_desc: bool,
+    /// Caption each example with its `**Step N**` number.
+    numbered: bool,
+    /// Wrap each embedded item in the modules it is nested in.
+    with_module_path: bool,
+    /// Prepend the top-level `use` items of the file that embedded items refer to.
+    with_imports: bool,
+    /// Include the comment lines immediately preceding each embedded item.
+    with_preamble: bool,
+    /// Annotate embedded constants with the value of their (literal) expression.
+    eval_const: bool,
+    /// Embed only the initializer expression of matching constants and `let` statements.
+    value_only: bool,
+    /// Embed only the contents of matching items, as if exported with `export_content`.
+    contents_only: bool,
+    /// Wrap embedded code that isn't made up of complete items in a `fn main() { .. }`.
+    wrap_main: bool,
+    /// Don't check that examples compiled as doc tests parse as valid rust.
+    no_check: bool,
+    /// Check that each example parses as valid rust, which is the case for examples compiled as
+    /// doc tests unless `no_check` is specified. Not an option in its own right.
+    check_syntax: bool,
+    /// The hash (see [`code_hash`]) that the embedded code must match.
+    hash: Option<String>,
+    /// The checksum that a file embedded from a URL must match.
+    sha256: Option<String>,
+    /// Treat the source file as a non-rust file with this codeblock language.
+    lang: Option<String>,
+    /// Use this string after the opening "```" of each example instead of the default.
+    fence: Option<String>,
+    /// Mark each example as using this rust edition, i.e. `2021`, for rustdoc.
+    edition: Option<String>,
+    /// Wrap comment lines of the embedded code that are longer than this many characters.
+    max_width: Option<usize>,
+    /// Prefix every line of the output, including the fences, with this many spaces.
+    indent: Option<usize>,
+    /// Label each example with this title.
+    title: Option<String>,
+    /// Where the `title` of each example is placed.
+    title_style: TitleStyle,
+    /// Emphasize the lines of each example containing this identifier.
+    highlight: Option<String>,
+    /// Use this instead of `// ...` in place of elided code.
+    placeholder: Option<String>,
+    /// The order in which several matching items are embedded.
+    sort: SortOrder,
+    /// Embed only these lines of each matching item, counting from the first line of the item.
+    lines: Option<LineRange>,
+    /// Embed every item exported with this `category = ".."`.
+    category: Option<String>,
+    /// Embed every item exported under a name matching this regex.
+    regex: Option<Regex>,
+    /// Embed the regions between `docify:begin-{marker}` and `docify:end-{marker}` comments.
+    marker: Option<String>,
+    /// Embed the whole file from the line after the first line containing this text onward.
+    from_marker: Option<String>,
+    /// Embed the regions between `docify:region-start {region}` and `docify:region-end`
+    /// comments.
+    region: Option<String>,
+}
+
+impl EmbedOptions {
+    /// The comment that takes the place of elided code, such as hidden regions.
+    fn placeholder(&self) -> &str {
+        self.placeholder
+            .as_deref()
+            .unwrap_or(HIDDEN_REGION.placeholder)
+    }
+
+    fn from_options(options: &[EmbedOption]) -> Result<EmbedOptions> {
+        let mut resolved = EmbedOptions::default();
+        for (i, option) in options.iter().enumerate() {
+            if options[..i].iter().any(|other| other.name == option.name) {
+                return Err(Error::new(
+                    option.name.span(),
+                    format!("The `{}` option is specified more than once.", option.name),
+                ));
+            }
+            match option.name.to_string().as_str() {
+                "unique" => resolved.unique = option.flag()?,
+                "workspace_relative" => resolved.workspace_relative = option.flag()?,
+                "path_env" => {
+                    let var = option.string()?;
+                    if options
+                        .iter()
+                        .any(|other| other.name == "workspace_relative")
+                    {
+                        return Err(Error::new(
+                            option.span(),
+                            "The `path_env` option cannot be combined with the \
+                            `workspace_relative` option.",
+                        ));
+                    }
+                    let dir = std::env::var_os(&var).ok_or_else(|| {
+                        Error::new(
+                            option.span(),
+                            format!(
+                                "The environment variable `{}` specified by the `path_env` \
+                                option is not set.",
+                                var
+                            ),
+                        )
+                    })?;
+                    resolved.path_env = Some((var, PathBuf::from(dir)));
+                }
+                "show_source" => resolved.show_source = option.flag()?,
+                "dedent" => resolved.dedent = option.flag()?,
+                "signature_only" => resolved.signature_only = option.flag()?,
+                "signatures_only" => resolved.signatures_only = option.flag()?,
+                "respect_cfg" => resolved.respect_cfg = option.flag()?,
+                "strip_docs" => resolved.strip_docs = option.flag()?,
+                "keep_doc_hidden" => resolved.keep_doc_hidden = option.flag()?,
+                "no_attrs" => resolved.no_attrs = option.flag()?,
+                "strip_test" => resolved.strip_test = option.flag()?,
+                "pub_only" => resolved.pub_only = option.flag()?,
+                "line_numbers" => resolved.line_numbers = option.flag()?,
+                "single_fence" => resolved.single_fence = option.flag()?,
+                "raw" => resolved.raw = option.flag()?,
+                "emit_metadata" => resolved.emit_metadata = option.flag()?,
+                "show_desc" => resolved.show_desc = option.flag()?,
+                "numbered" => resolved.numbered = option.flag()?,
+                "with_module_path" => resolved.with_module_path = option.flag()?,
+                "with_imports" => resolved.with_imports = option.flag()?,
+                "with_preamble" => resolved.with_preamble = option.flag()?,
+                "eval_const" => resolved.eval_const = option.flag()?,
+                "value_only" => resolved.value_only = option.flag()?,
+                "contents_only" => resolved.contents_only = option.flag()?,
+                "wrap_main" => resolved.wrap_main = option.flag()?,
+                "no_check" => resolved.no_check = option.flag()?,
+                "hash" => resolved.hash = Some(option.string()?),
+                "strip_attrs" => {
+                    let paths = option.string()?;
+                    resolved.strip_attrs = paths
+                        .split(',')
+                        .map(|path| path.chars().filter(|c| !c.is_whitespace()).collect())
+                        .collect();
+                    if resolved.strip_attrs.iter().any(String::is_empty) {
+                        return Err(Error::new(
+                            option.span(),
+                            "The `strip_attrs` option must be a comma-separated list of \
+                            attribute paths, i.e. \"tokio::main,serde\".",
+                        ));
+                    }
+                }
+                "sha256" => resolved.sha256 = Some(option.string()?),
+                "max_width" => resolved.max_width = Some(option.positive_int()?),
+                "indent" => {
+                    let indent = option.positive_int()?;
+                    if indent >= 64 {
+                        return Err(Error::new(
+                            option.span(),
+                            "The `indent` option must be less than 64.",
+                        ));
+                    }
+                    resolved.indent = Some(indent);
+                }
+                "lang" => resolved.lang = Some(option.string()?),
+                "marker" => resolved.marker = Some(option.string()?),
+                "region" => {
+                    let region = option.string()?;
+                    if region.is_empty() || region.contains(char::is_whitespace) {
+                        return Err(Error::new(
+                            option.span(),
+                            "The `region` option must be a single word, i.e. \"payload\".",
+                        ));
+                    }
+                    resolved.region = Some(region);
+                }
+                "from_marker" => {
+                    let sentinel = option.string()?;
+                    if sentinel.trim().is_empty() || sentinel.contains(['\n', '\r']) {
+                        return Err(Error::new(
+                            option.span(),
+                            "The `from_marker` option must be a single, non-blank line.",
+                        ));
+                    }
+                    resolved.from_marker = Some(sentinel);
+                }
+                "category" => resolved.category = Some(option.string()?),
+                "regex" => {
+                    let regex = Regex::new(&option.string()?).map_err(|err| {
+                        Error::new(option.span(), format!("Invalid `regex` option: {}", err))
+                    })?;
+                    resolved.regex = Some(regex);
+                }
+                "lines" => resolved.lines = Some(option.line_range()?),
+                "placeholder" => {
+                    let placeholder = option.string()?;
+                    if placeholder.trim().is_empty() || placeholder.contains(['\n', '\r']) {
+                        return Err(Error::new(
+                            option.span(),
+                            "The `placeholder` option must be a single, non-blank line.",
+                        ));
+                    }
+                    resolved.placeholder = Some(placeholder);
+                }
+                "fence" => {
+                    let fence = option.string()?;
+                    if fence.contains(['\n', '\r', '`']) {
+                        return Err(Error::new(
+                            option.span(),
+                            "The `fence` option cannot contain newlines or backticks.",
+                        ));
+                    }
+                    resolved.fence = Some(fence);
+                }
+                "edition" => {
+                    let edition = option.string()?;
+                    if !["2015", "2018", "2021", "2024"].contains(&edition.as_str()) {
+                        return Err(Error::new(
+                            option.span(),
+                            "The `edition` option must be one of \"2015\", \"2018\", \"2021\", \
+                            or \"2024\".",
+                        ));
+                    }
+                    resolved.edition = Some(edition);
+                }
+                "title" => {
+                    let title = option.string()?;
+                    if title.contains(['\n', '\r', '`']) {
+                        return Err(Error::new(
+                            option.span(),
+                            "The `title` option cannot contain newlines or backticks.",
+                        ));
+                    }
+                    resolved.title = Some(title);
+                }
+                "sort" => {
+                    resolved.sort = match option.string()?.as_str() {
+                        "source" => SortOrder::Source,
+                        "reverse" => SortOrder::Reverse,
+                        "name" => SortOrder::Name,
+                        _ => {
+                            return Err(Error::new(
+                                option.span(),
+                                "The `sort` option must be one of \"source\", \"reverse\", or \
+                                \"name\".",
+                            ))
+                        }
+                    }
+                }
+                "highlight" => {
+                    let highlight = option.string()?;
+                    if syn::parse_str::<Ident>(&highlight).is_err() {
+                        return Err(Error::new(
+                            option.span(),
+                            "The `highlight` option must be an identifier, such as \"client\".",
+                        ));
+                    }
+                    resolved.highlight = Some(highlight);
+                }
+                "title_style" => {
+                    resolved.title_style =
+                        match option.string()?.as_str() {
+                            "comment" => TitleStyle::Comment,
+                            "fence" => TitleStyle::Fence,
+                            _ => return Err(Error::new(
+                                option.span(),
+                                "The `title_style` option must be either \"comment\" or \"fence\".",
+                            )),
+                        }
+                }
+                _ => {
+                    return Err(Error::new(
+                        option.name.span(),
+                        format!("Unknown `docify::embed!(..)` option `{}`.", option.name),
+                    ))
+                }
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+/// The order in which several matching items are embedded, see [`EmbedOptions::sort`].
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+enum SortOrder {
+    /// In the order they appear in the source file.
+    #[default]
+    Source,
+    /// In the reverse of the order they appear in the source file.
+    Reverse,
+    /// Alphabetically by the inherent name of each item.
+    Name,
+}
+
+/// Where the `title` of an example is placed, see [`EmbedOptions::title`].
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+enum TitleStyle {
+    /// As a `// title` comment on the first line of the example.
+    #[default]
+    Comment,
+    /// As a `title=..` attribute after the opening "```" of the example.
+    Fence,
+}
+
+/// The (optional) second argument of `docify::embed!(..)`, specifying what part of the
+/// source file should be embedded.
+#[derive(Clone)]
+enum EmbedTarget {
+    Lines(LineRange),
+    Item(Ident),
+    /// A path such as `MyType::my_method`, referring to a member of a type.
+    Path(SynPath),
+}
+
+impl Parse for EmbedTarget {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(LitInt) {
+            return Ok(EmbedTarget::Lines(input.parse()?));
+        }
+        if input.fork().parse::<Ident>().is_err() {
+            if let Ok(keyword) = input.fork().call(Ident::parse_any) {
+                let name = keyword.to_string();
+                if !["self", "super", "crate", "Self", "_"].contains(&name.as_str()) {
+                    return Err(Error::new(
+                        keyword.span(),
+                        format!(
+                            "`{}` is a keyword, so an item named `{}` must be referred to by \
+                            its raw identifier, i.e. `r#{}`.",
+                            name, name, name
+                        ),
+                    ));
+                }
+            }
+        }
+        let path = input.call(SynPath::parse_mod_style)?;
+        match path.get_ident() {
+            Some(ident) => Ok(EmbedTarget::Item(ident.clone())),
+            None => Ok(EmbedTarget::Path(path)),
+        }
+    }
+}
+
+impl ToTokens for EmbedTarget {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        match self {
+            EmbedTarget::Lines(range) => range.to_tokens(tokens),
+            EmbedTarget::Item(ident) => ident.to_tokens(tokens),
+            EmbedTarget::Path(path) => path.to_tokens(tokens),
+        }
+    }
+}
+
+impl EmbedTarget {
+    /// Returns a human-readable name for this target, for use in error messages.
+    fn name(&self) -> String {
+        match self {
+            EmbedTarget::Lines(range) => range.to_token_stream().to_string(),
+            EmbedTarget::Item(ident) => ident.to_string(),
+            EmbedTarget::Path(path) => path
+                .segments
+                .iter()
+                .map(|seg| seg.ident.to_string())
+                .collect::<Vec<String>>()
+                .join("::"),
+        }
+    }
+}
+
+/// A range of 1-based line numbers, such as `10..25` or `10..=25`
+#[derive(Clone, Parse)]
+struct LineRange {
+    start: LitInt,
+    limits: RangeLimits,
+    end: LitInt,
+}
+
+impl ToTokens for LineRange {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        self.start.to_tokens(tokens);
+        self.limits.to_tokens(tokens);
+        self.end.to_tokens(tokens);
+    }
+}
+
+impl LineRange {
+    /// Returns the lines of `source` covered by this range, joined with `\n`. Issues a compile
+    /// error at the span of the range if it is empty or out of bounds, in which case `source`
+    /// is referred to as `what`, i.e. "file".
+    fn excerpt<S: AsRef<str>>(&self, source: S, what: &str) -> Result<String> {
+        let source = source.as_ref();
+        let start = self.start.base10_parse::<usize>()?;
+        let end = self.end.base10_parse::<usize>()?;
+        let end = match self.limits {
+            RangeLimits::HalfOpen(_) => end,
+            RangeLimits::Closed(_) => end + 1,
+        };
+        let num_lines = source.lines().count();
+        if start == 0 {
+            return Err(Error::new_spanned(
+                self,
+                "Line numbers are 1-based, so the range cannot start at line 0.",
+            ));
+        }
+        if end <= start {
+            return Err(Error::new_spanned(
+                self,
+                "The specified line range is empty.",
+            ));
+        }
+        if end - 1 > num_lines {
+            return Err(Error::new_spanned(
+                self,
+                format!(
+                    "The specified line range is out of bounds, the {} only has {} lines.",
+                    what, num_lines
+                ),
+            ));
+        }
+        Ok(source
+            .lines()
+            .skip(start - 1)
+            .take(end - start)
+            .collect::<Vec<&str>>()
+            .join("\n"))
+    }
+}
+
+mod keywords {
+    use syn::custom_keyword;
+
+    custom_keyword!(docify);
+    custom_keyword!(embed);
+}
+
+/// Used to parse a full `docify::embed!(..)` call, as seen in markdown documents and other
+/// embedded settings
+#[derive(Parse)]
+struct EmbedCommentCall {
+    #[prefix(keywords::docify)]
+    #[prefix(Token![::])]
+    #[prefix(keywords::embed)]
+    #[prefix(Token![!])]
+    #[paren]
+    _paren: Paren,
+    #[inside(_paren)]
+    args: EmbedArgs,
+    _semi: Option<Token![;]>,
+}
+
+/// This corresponds with the string immediately following the "```" in codeblocks. Every
+/// language is marked as `rust` so that renderers other than rustdoc highlight the example.
+/// Blank means no other attributes are specified, so the example is run as a doc test, Ignore
+/// will cause the example not to run in rust docs, and NoRun will cause it to be compiled but
+/// not run.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum MarkdownLanguage {
+    Ignore,
+    NoRun,
+    Rust,
+    Blank,
+}
+
+impl MarkdownLanguage {
+    /// The string that follows the opening "```" of a codeblock in this language.
+    fn fence_info(self) -> &'static str {
+        match self {
+            MarkdownLanguage::Ignore => "rust,ignore",
+            MarkdownLanguage::NoRun => "rust,no_run",
+            MarkdownLanguage::Rust | MarkdownLanguage::Blank => "rust",
+        }
+    }
+}
+
+/// Like [`into_fenced_example`], but labels the example with the specified title, if any, in
+/// the specified [`TitleStyle`].
+fn into_titled_example(st: &str, info: &str, title: Option<&str>, style: TitleStyle) -> String {
+    match (title, style) {
+        (None, _) => into_fenced_example(st, info),
+        (Some(title), TitleStyle::Comment) => {
+            into_fenced_example(&format!("// {}\n{}", title, st), info)
+        }
+        (Some(title), TitleStyle::Fence) if info.is_empty() => {
+            into_fenced_example(st, &format!("title={}", title))
+        }
+        (Some(title), TitleStyle::Fence) => {
+            into_fenced_example(st, &format!("{},title={}", info, title))
+        }
     }
-    for line in st.lines() {
-        lines.push(String::from(line));
+}
+
+/// Converts a source string to a codeblocks wrapped example, with `info` following the
+/// opening "```". Blank lines at the very start and end of the source are left out, while blank
+/// lines in between are kept. As in CommonMark, if the source itself contains a run of three or
+/// more backticks, such as a fenced example inside a string literal, the fence is made one
+/// backtick longer than the longest such run so the source can't end the codeblock early.
+/// Trailing whitespace is trimmed from each line, see [`trim_trailing_whitespace`].
+fn into_fenced_example(st: &str, info: &str) -> String {
+    let st = &trim_trailing_whitespace(st);
+    let longest_run = st
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(max(3, longest_run + 1));
+    let mut lines: Vec<String> = Vec::new();
+    lines.push(format!("{}{}", fence, info));
+    let content: Vec<&str> = st.lines().collect();
+    let start = content
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .unwrap_or(content.len());
+    let end = content
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(start, |end| end + 1);
+    for line in &content[start..end] {
+        lines.push(String::from(*line));
     }
-    lines.push(String::from("```"));
+    lines.push(fence);
     lines.join("\n")
 }
 
+/// Trims the trailing whitespace from each line of the specified source, which whitespace
+/// linters would otherwise flag in generated files, except for lines ending inside a string
+/// literal, where the whitespace is part of the value of the literal.
+fn trim_trailing_whitespace(source: &str) -> String {
+    let literals = literal_positions(source);
+    let mut trimmed = String::new();
+    let mut start = 0;
+    for line in source.split('\n') {
+        let end = start + line.len();
+        match literals.get(end).copied().unwrap_or(false) {
+            true => trimmed.push_str(line),
+            false => trimmed.push_str(line.trim_end()),
+        }
+        if end < source.len() {
+            trimmed.push('\n');
+        }
+        start = end + 1;
+    }
+    trimmed
+}
+
 /// Generalizes over items that we support exporting via Docify, used by [`ItemVisitor`].
 trait SupportedVisitItem<'ast> {
     fn visit_supported_item<T: NamedItem + AttributedItem + ToTokens + Clone>(
@@ -619,10 +2381,14 @@ impl<'ast> SupportedVisitItem<'ast> for ItemVisitor {
         &mut self,
         node: &'ast T,
     ) {
-        let mut i = 0;
         let attrs = node.item_attributes();
-        for attr in attrs {
-            i += 1; // note, 1-based
+        let expanded = attrs.iter().enumerate().flat_map(|(i, attr)| {
+            expand_cfg_attr(attr, self.respect_cfg)
+                .into_iter()
+                .map(move |attr| (i, attr))
+        });
+        for (i, attr) in expanded {
+            let attr = &attr;
             let AttrStyle::Outer = attr.style else {
                 continue;
             };
@@ -642,32 +2408,71 @@ impl<'ast> SupportedVisitItem<'ast> for ItemVisitor {
             // we have found a #[something::docify::export] or #[docify::export] or
             // #[export]-style attribute
             // (OR any of the above but export_content)
+            self.found_exports = true;
 
-            // resolve item_ident
-            let item_ident = match &attr.meta {
-                Meta::List(list) => match parse2::<Ident>(list.tokens.clone()) {
-                    Ok(ident) => Some(ident),
-                    Err(_) => None,
-                },
-                _ => None,
-            };
-            let item_ident = match item_ident {
-                Some(ident) => ident,
-                None => match node.name_ident() {
-                    Some(ident) => ident,
-                    None => continue,
-                },
+            // resolve item_idents (an item can be exported under several aliases), recording
+            // malformed export names rather than falling back to the inherent ident
+            let (item_idents, category, desc): (Vec<Ident>, Option<String>, Option<String>) =
+                match &attr.meta {
+                    // rustc passes the same empty tokens to `#[export]` and `#[export()]`, so empty
+                    // parentheses can only be caught here rather than by the attribute itself
+                    Meta::List(list) if list.tokens.is_empty() => {
+                        self.errors.push(Error::new_spanned(
+                        list,
+                        "Empty parentheses are not allowed, either specify an export name, i.e. \
+                        #[docify::export(my_name)], or omit the parentheses to use the item's \
+                        own name.",
+                    ));
+                        continue;
+                    }
+                    Meta::List(list) => match list.parse_args::<ExportAttr>() {
+                        Ok(export) => (
+                            export.idents,
+                            export.category.map(|lit| lit.value()),
+                            export.desc.map(|lit| lit.value()),
+                        ),
+                        Err(err) => {
+                            self.errors.push(err);
+                            continue;
+                        }
+                    },
+                    Meta::NameValue(name_value) => {
+                        self.errors.push(Error::new_spanned(
+                            name_value,
+                            "Export names must be specified in parentheses, i.e. \
+                        #[docify::export(my_name)].",
+                        ));
+                        continue;
+                    }
+                    Meta::Path(_) => (Vec::new(), None, None),
+                };
+            let item_idents = match item_idents.is_empty() {
+                false => item_idents,
+                true => node.name_ident().into_iter().collect(),
             };
 
-            // check if this ident matches the one we're searching for
-            if item_ident == self.search {
+            self.candidates.extend(item_idents.iter().cloned());
+
+            // check if any of these idents (or the category) match what we're searching for
+            let matched = match &self.search {
+                ItemSearch::Name(search) => {
+                    item_idents.iter().any(|ident| same_ident(ident, search))
+                }
+                ItemSearch::Category(search) => category.as_deref() == Some(search.as_str()),
+                ItemSearch::Pattern(regex) => item_idents
+                    .iter()
+                    .any(|ident| regex.is_match(&ident.unraw().to_string())),
+                ItemSearch::Nothing => false,
+            };
+            if matched && self.in_scope() {
                 let mut item = node.clone();
                 // modify item's attributes to not include this one so this one is excluded
-                // from the code example
+                // from the code example (`i` is the 0-based index of this attribute among all
+                // of the item's attributes, not just its export attributes)
                 let attrs_without_this_one: Vec<Attribute> = attrs
                     .iter()
                     .enumerate()
-                    .filter(|&(n, _)| n != i - 1)
+                    .filter(|&(n, _)| n != i)
                     .map(|(_, v)| v)
                     .cloned()
                     .collect();
@@ -679,6 +2484,8 @@ impl<'ast> SupportedVisitItem<'ast> for ItemVisitor {
                         true => ResultStyle::ExportContent,
                         false => ResultStyle::Export,
                     },
+                    self.modules.clone(),
+                    desc,
                 ));
                 // no need to explore the attributes of this item further, it is already in results
                 break;
@@ -687,370 +2494,3849 @@ impl<'ast> SupportedVisitItem<'ast> for ItemVisitor {
     }
 }
 
+/// Returns `true` if the specified idents refer to the same name, treating a raw ident such as
+/// `r#type` as the same as `type`, just like rustc does.
+fn same_ident(a: &Ident, b: &Ident) -> bool {
+    a.unraw() == b.unraw()
+}
+
+/// An item found by an [`ItemVisitor`] or [`MemberVisitor`], along with how it should be
+/// excerpted, the path of the module it was found in, and its `desc = ".."`, if any.
+type FoundItem = (TokenStream2, ResultStyle, Vec<Ident>, Option<String>);
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 enum ResultStyle {
     Export,
     ExportContent,
+    /// An enum variant, which is embedded inside an elided copy of its enum.
+    Variant,
+}
+
+/// What an [`ItemVisitor`] is searching for.
+enum ItemSearch {
+    /// Items exported under this name.
+    Name(Ident),
+    /// Items exported with this `category = ".."`.
+    Category(String),
+    /// Items exported under a name matching this regex.
+    Pattern(Regex),
+    /// No items, for when only the names collected in [`ItemVisitor::candidates`] are needed.
+    Nothing,
 }
 
 /// Visitor pattern for finding items
 struct ItemVisitor {
-    search: Ident,
-    results: Vec<(TokenStream2, ResultStyle)>,
+    search: ItemSearch,
+    /// If specified, only items directly inside this module path (relative to the root of the
+    /// file) are matched, otherwise items are matched in any scope.
+    scope: Option<Vec<Ident>>,
+    /// The path of the module currently being visited.
+    modules: Vec<Ident>,
+    /// Skip items (and their contents) whose `#[cfg(..)]` is inactive, see [`cfg_active`].
+    respect_cfg: bool,
+    /// Each matching item.
+    results: Vec<FoundItem>,
+    /// Errors for malformed export attributes encountered anywhere in the file.
+    errors: Vec<Error>,
+    /// Every export name encountered in the file, used to suggest near-misses.
+    candidates: Vec<Ident>,
+    /// Whether any export attribute (or `docify::export_stmt!(..)`) was encountered in the file
+    /// at all, well-formed or not.
+    found_exports: bool,
+}
+
+impl ItemVisitor {
+    fn new(search: ItemSearch, scope: Option<Vec<Ident>>) -> ItemVisitor {
+        ItemVisitor {
+            search,
+            scope,
+            modules: Vec::new(),
+            respect_cfg: false,
+            results: Vec::new(),
+            errors: Vec::new(),
+            candidates: Vec::new(),
+            found_exports: false,
+        }
+    }
+
+    /// Returns `true` if the specified item should be skipped because of its `#[cfg(..)]`.
+    fn cfg_excluded<T: AttributedItem>(&self, node: &T) -> bool {
+        self.respect_cfg && !cfg_active(node.item_attributes())
+    }
+
+    /// Returns `true` if the module currently being visited is within the requested scope.
+    fn in_scope(&self) -> bool {
+        match &self.scope {
+            Some(scope) => *scope == self.modules,
+            None => true,
+        }
+    }
 }
 
 impl<'ast> Visit<'ast> for ItemVisitor {
     fn visit_trait_item(&mut self, node: &'ast TraitItem) {
+        if self.cfg_excluded(node) {
+            return;
+        }
         self.visit_supported_item(node);
         visit::visit_trait_item(self, node);
     }
 
+    fn visit_foreign_item(&mut self, node: &'ast ForeignItem) {
+        if self.cfg_excluded(node) {
+            return;
+        }
+        self.visit_supported_item(node);
+        visit::visit_foreign_item(self, node);
+    }
+
     fn visit_impl_item(&mut self, node: &'ast ImplItem) {
+        if self.cfg_excluded(node) {
+            return;
+        }
         self.visit_supported_item(node);
         visit::visit_impl_item(self, node);
     }
 
     fn visit_item(&mut self, node: &'ast Item) {
+        if self.cfg_excluded(node) {
+            return;
+        }
         self.visit_supported_item(node);
         visit::visit_item(self, node);
     }
+
+    fn visit_stmt(&mut self, node: &'ast Stmt) {
+        if self.cfg_excluded(node) {
+            return;
+        }
+        self.visit_supported_item(node);
+        visit::visit_stmt(self, node);
+    }
+
+    fn visit_item_mod(&mut self, node: &'ast ItemMod) {
+        self.modules.push(node.ident.clone());
+        visit::visit_item_mod(self, node);
+        self.modules.pop();
+    }
+
+    fn visit_macro(&mut self, node: &'ast Macro) {
+        if is_export_stmt(&node.path) {
+            self.found_exports = true;
+            match node.parse_body::<ExportStmtArgs>() {
+                Ok(args) => {
+                    self.candidates.push(args.name.clone());
+                    let matched = match &self.search {
+                        ItemSearch::Name(search) => same_ident(&args.name, search),
+                        ItemSearch::Pattern(regex) => {
+                            regex.is_match(&args.name.unraw().to_string())
+                        }
+                        ItemSearch::Category(_) | ItemSearch::Nothing => false,
+                    };
+                    if matched && self.in_scope() {
+                        // only the wrapped statements are embedded, not the macro invocation
+                        self.results.push((
+                            args.stmts,
+                            ResultStyle::Export,
+                            self.modules.clone(),
+                            None,
+                        ));
+                    }
+                }
+                Err(err) => self.errors.push(err),
+            }
+        }
+        visit::visit_macro(self, node);
+    }
 }
 
-/// Abstraction for a character that has been transposed/offset from its original position in
-/// the original string in which it appeared (i.e. if the string has been compressed in some way)
-#[derive(Copy, Clone, Eq, PartialEq)]
+/// Visitor pattern for finding items marked with `#[docify::hide]`, which are left out of
+/// whole-file embeds.
+#[derive(Default)]
+struct HiddenItemVisitor {
+    /// The tokens of each hidden item, excluding the `#[docify::hide]` attribute itself.
+    hidden: Vec<TokenStream2>,
+}
+
+impl HiddenItemVisitor {
+    /// Records the specified item if it is marked with `#[docify::hide]`, returning `true` if
+    /// so, in which case its contents don't need to be visited.
+    fn visit_hidden_item<T: AttributedItem + ToTokens + Clone>(&mut self, node: &T) -> bool {
+        let attrs = node.item_attributes();
+        let is_hide = |attr: &Attribute| {
+            let segments = attr.path().segments.iter().rev().collect::<Vec<_>>();
+            matches!(attr.style, AttrStyle::Outer)
+                && segments.len() >= 2
+                && segments[0].ident == "hide"
+                && segments[1].ident == "docify"
+        };
+        if !attrs.iter().any(is_hide) {
+            return false;
+        }
+        let mut item = node.clone();
+        item.set_item_attributes(
+            attrs
+                .iter()
+                .filter(|attr| !is_hide(attr))
+                .cloned()
+                .collect(),
+        );
+        self.hidden.push(item.to_token_stream());
+        true
+    }
+}
+
+impl<'ast> Visit<'ast> for HiddenItemVisitor {
+    fn visit_item(&mut self, node: &'ast Item) {
+        if !self.visit_hidden_item(node) {
+            visit::visit_item(self, node);
+        }
+    }
+
+    fn visit_impl_item(&mut self, node: &'ast ImplItem) {
+        if !self.visit_hidden_item(node) {
+            visit::visit_impl_item(self, node);
+        }
+    }
+
+    fn visit_trait_item(&mut self, node: &'ast TraitItem) {
+        if !self.visit_hidden_item(node) {
+            visit::visit_trait_item(self, node);
+        }
+    }
+}
+
+/// Visitor pattern for finding members of types by the name of the type, i.e. members of `impl`
+/// blocks such as `MyType::my_method` or `MyType::MY_CONST` and enum variants such as `MyEnum::MyVariant`. Unlike
+/// [`ItemVisitor`], members found this way do not need to be marked with `#[docify::export]`.
+struct MemberVisitor {
+    self_ty: Ident,
+    member: Ident,
+    /// If specified, only types and `impl` blocks directly inside this module path are
+    /// searched, as with
+    /// [`ItemVisitor::scope`].
+    scope: Option<Vec<Ident>>,
+    modules: Vec<Ident>,
+    /// As with [`ItemVisitor::respect_cfg`].
+    respect_cfg: bool,
+    /// As with [`ItemVisitor::results`].
+    results: Vec<FoundItem>,
+}
+
+impl MemberVisitor {
+    /// Creates a visitor for a path such as `MyType::member` or `my_mod::MyType::member`,
+    /// returning `None` if the path has fewer than two segments.
+    fn new(path: &SynPath) -> Option<MemberVisitor> {
+        let mut segments: Vec<Ident> = path.segments.iter().map(|seg| seg.ident.clone()).collect();
+        let member = segments.pop()?;
+        let self_ty = segments.pop()?;
+        Some(MemberVisitor {
+            self_ty,
+            member,
+            scope: (!segments.is_empty()).then_some(segments),
+            modules: Vec::new(),
+            respect_cfg: false,
+            results: Vec::new(),
+        })
+    }
+
+    /// Returns `true` if the module currently being visited is within the requested scope.
+    fn in_scope(&self) -> bool {
+        match &self.scope {
+            Some(scope) => *scope == self.modules,
+            None => true,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for MemberVisitor {
+    fn visit_item(&mut self, node: &'ast Item) {
+        if self.respect_cfg && !cfg_active(node.item_attributes()) {
+            return;
+        }
+        visit::visit_item(self, node);
+    }
+
+    fn visit_item_mod(&mut self, node: &'ast ItemMod) {
+        self.modules.push(node.ident.clone());
+        visit::visit_item_mod(self, node);
+        self.modules.pop();
+    }
+
+    fn visit_item_enum(&mut self, node: &'ast ItemEnum) {
+        if same_ident(&node.ident, &self.self_ty) && self.in_scope() {
+            for variant in &node.variants {
+                if self.respect_cfg && !cfg_active(&variant.attrs) {
+                    continue;
+                }
+                if same_ident(&variant.ident, &self.member) {
+                    self.results.push((
+                        variant.to_token_stream(),
+                        ResultStyle::Variant,
+                        self.modules.clone(),
+                        None,
+                    ));
+                }
+            }
+        }
+        visit::visit_item_enum(self, node);
+    }
+
+    fn visit_item_impl(&mut self, node: &'ast ItemImpl) {
+        let self_ty_ident = match &*node.self_ty {
+            Type::Path(type_path) => type_path.path.segments.last().map(|seg| &seg.ident),
+            _ => None,
+        };
+        if self_ty_ident.is_some_and(|ident| same_ident(ident, &self.self_ty)) && self.in_scope() {
+            // methods, associated consts, and associated types are all named members
+            for impl_item in &node.items {
+                if self.respect_cfg && !cfg_active(impl_item.item_attributes()) {
+                    continue;
+                }
+                if impl_item
+                    .name_ident()
+                    .is_some_and(|ident| same_ident(&ident, &self.member))
+                {
+                    self.results.push((
+                        impl_item.to_token_stream(),
+                        ResultStyle::Export,
+                        self.modules.clone(),
+                        None,
+                    ));
+                }
+            }
+        }
+        visit::visit_item_impl(self, node);
+    }
+}
+
+/// Returns `false` if any of the specified attributes is a `#[cfg(..)]` whose predicate is known
+/// to be inactive for the crate being compiled, see [`cfg_predicate_active`].
+fn cfg_active(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .all(|attr| {
+            attr.parse_args::<Meta>()
+                .ok()
+                .and_then(|predicate| cfg_predicate_active(&predicate))
+                .unwrap_or(true)
+        })
+}
+
+/// Returns the attributes that the specified attribute stands for, i.e. the attributes that a
+/// `#[cfg_attr(predicate, attr_a, attr_b)]` attribute applies, or just the attribute itself if
+/// it is any other attribute. If `respect_cfg` is `true`, no attributes are returned for a
+/// `cfg_attr` whose predicate is known to be inactive, as with [`cfg_active`].
+fn expand_cfg_attr(attr: &Attribute, respect_cfg: bool) -> Vec<Attribute> {
+    if !attr.path().is_ident("cfg_attr") {
+        return vec![attr.clone()];
+    }
+    let Ok(metas) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) else {
+        return Vec::new();
+    };
+    let mut metas = metas.into_iter();
+    let Some(predicate) = metas.next() else {
+        return Vec::new();
+    };
+    if respect_cfg && cfg_predicate_active(&predicate) == Some(false) {
+        return Vec::new();
+    }
+    metas
+        .map(|meta| Attribute {
+            meta,
+            ..attr.clone()
+        })
+        .collect()
+}
+
+/// Evaluates a `cfg` predicate made up of `feature = ".."`, `all(..)`, `any(..)` and `not(..)`
+/// against the `CARGO_FEATURE_*` environment variables, returning `None` if the predicate
+/// contains anything else, since then its value can't be known.
+fn cfg_predicate_active(predicate: &Meta) -> Option<bool> {
+    match predicate {
+        Meta::NameValue(name_value) if name_value.path.is_ident("feature") => {
+            let Expr::Lit(ExprLit {
+                lit: Lit::Str(feature),
+                ..
+            }) = &name_value.value
+            else {
+                return None;
+            };
+            let var = format!(
+                "CARGO_FEATURE_{}",
+                feature.value().to_uppercase().replace('-', "_")
+            );
+            Some(std::env::var_os(var).is_some())
+        }
+        Meta::List(list) => {
+            let values = list
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .ok()?
+                .iter()
+                .map(cfg_predicate_active)
+                .collect::<Option<Vec<bool>>>()?;
+            if list.path.is_ident("all") {
+                Some(values.iter().all(|value| *value))
+            } else if list.path.is_ident("any") {
+                Some(values.iter().any(|value| *value))
+            } else if list.path.is_ident("not") && values.len() == 1 {
+                Some(!values[0])
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Abstraction for a character that has been transposed/offset from its original position in
+/// the original string in which it appeared (i.e. if the string has been compressed in some way)
+#[derive(Copy, Clone, Eq, PartialEq)]
 struct OffsetChar {
     char: char,
     original_pos: usize,
 }
 
-impl OffsetChar {
-    fn new(char: char, original_pos: usize) -> OffsetChar {
-        OffsetChar { char, original_pos }
+impl OffsetChar {
+    fn new(char: char, original_pos: usize) -> OffsetChar {
+        OffsetChar { char, original_pos }
+    }
+}
+
+/// Used to mark an entity within a piece of source code. Used with [`CompressedString`].
+#[derive(Clone, PartialEq, Eq)]
+struct SourceEntity {
+    start: usize,
+    end: usize,
+}
+
+impl SourceEntity {
+    pub fn new(start: usize, end: usize) -> SourceEntity {
+        SourceEntity { start, end }
+    }
+
+    /// Marks the character positions corresponding with this entity as belonging to this
+    /// entity in the enclosing [`CompressedString`].
+    pub fn claim(&self, claimed: &mut [bool]) {
+        for i in self.start..min(self.end, claimed.len()) {
+            claimed[i] = true;
+        }
+    }
+
+    /// Returns `true` if this entity already appears in the specified claimed vec
+    pub fn is_claimed(&self, claimed: &[bool]) -> bool {
+        claimed[(self.start + self.end) / 2]
+    }
+
+    // pub fn value<'a>(&self, source: &'a String) -> &'a str {
+    //     &source.as_str()[self.start..self.end]
+    // }
+
+    // pub fn contains(&self, x: usize) -> bool {
+    //     x >= self.start && x < self.end
+    // }
+}
+
+/// Represents a [`String`] that has been compressed in some way, and includes data structures
+/// allowing us to map individual characters back to their original positions in the
+/// uncompressed version of the [`String`].
+struct CompressedString {
+    chars: HashMap<usize, OffsetChar>,
+    chars_arr: Vec<OffsetChar>,
+}
+
+impl std::fmt::Display for CompressedString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.chars_arr
+            .iter()
+            .try_for_each(|c| write!(f, "{}", c.char))
+    }
+}
+
+static DOCIFY_ATTRIBUTES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"\n?\#\s*\[\s*(?:(?:\w+\s*::\s*)*(?:export|export_content|hide)(?:\s*\(\s*(?:\w+\s*=\s*"(?:[^"\\]|\\.)*"|\w+)(?:\s*,\s*(?:\w+\s*=\s*"(?:[^"\\]|\\.)*"|\w+))*\s*,?\s*\))?|cfg_attr\s*\([^\[\]]*?\bdocify\s*::\s*(?:export|export_content)\b[^\[\]]*\))\s*\]\n?"#,
+    )
+    .unwrap()
+});
+
+static DOC_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"///.*").unwrap());
+static DOC_COMMENT_ATTR: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"#\s*\[\s*doc\s*=\s*"(?:[^"\\]|\\[\s\S])*"\s*]"#).unwrap());
+static ATTRIBUTE_START: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"#\s*\[\s*((?:\w+\s*::\s*)*\w+)").unwrap());
+static LINE_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"//.*").unwrap());
+static MULTI_LINE_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"/\*[\s\S]*?\*/").unwrap());
+static HTML_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"<!--[\s\S]*?-->").unwrap());
+static EMBED_CALL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bembed(_run|_no_run|_docs|_diff)?!\s*\(").unwrap());
+static MARKDOWN_CODEBLOCK: Lazy<Regex> = Lazy::new(|| Regex::new(r"```[\s\S]*?```").unwrap());
+// static ref STRING_LIT: Regex = Regex::new(r#"("([^"\\]|\\[\s\S])*")"#).unwrap();
+
+impl From<&String> for CompressedString {
+    fn from(value: &String) -> Self {
+        let mut entities: Vec<SourceEntity> = Vec::new();
+        let mut claimed: Vec<bool> = vec![false; value.len()];
+        let literals = literal_positions(value);
+        for m in find_outside_literals(&DOC_COMMENT, value, &literals) {
+            let entity = SourceEntity::new(m.start(), m.end());
+            entity.claim(&mut claimed);
+            entities.push(entity);
+        }
+        for m in find_outside_literals(&DOC_COMMENT_ATTR, value, &literals) {
+            let entity = SourceEntity::new(m.start(), m.end());
+            if !entity.is_claimed(&claimed) {
+                entity.claim(&mut claimed);
+                entities.push(entity);
+            }
+        }
+        for m in find_outside_literals(&MULTI_LINE_COMMENT, value, &literals) {
+            let entity = SourceEntity::new(m.start(), m.end());
+            if !entity.is_claimed(&claimed) {
+                entity.claim(&mut claimed);
+                entities.push(entity);
+            }
+        }
+        for m in find_outside_literals(&LINE_COMMENT, value, &literals) {
+            let entity = SourceEntity::new(m.start(), m.end());
+            if !entity.is_claimed(&claimed) {
+                entity.claim(&mut claimed);
+                entities.push(entity);
+            }
+        }
+        for m in find_outside_literals(&DOCIFY_ATTRIBUTES, value, &literals) {
+            let entity = SourceEntity::new(m.start(), m.end());
+            if !entity.is_claimed(&claimed) {
+                entity.claim(&mut claimed);
+                entities.push(entity);
+            }
+        }
+        let mut compressed = CompressedString {
+            chars_arr: Vec::new(),
+            chars: HashMap::new(),
+        };
+        let mut cursor = 0;
+        let mut byte_index = 0;
+        while byte_index < value.len() {
+            let current_char = &value[byte_index..].chars().next().unwrap(); // get the current character
+            let char_len = current_char.len_utf8(); // get its length in bytes
+
+            if claimed[byte_index] || current_char.is_whitespace() {
+                byte_index += char_len;
+                continue;
+            }
+            let oc = OffsetChar::new(*current_char, byte_index);
+            compressed.chars.insert(cursor, oc);
+            compressed.chars_arr.push(oc);
+            cursor += 1;
+            byte_index += char_len;
+        }
+
+        compressed
+    }
+}
+
+/// Returns, for each byte of `source`, whether it is part of a string or character literal, so
+/// that comment-like text inside literals (i.e. `"https://"`) is not mistaken for a comment.
+fn literal_positions(source: &str) -> Vec<bool> {
+    let bytes = source.as_bytes();
+    let mut literals = vec![false; bytes.len()];
+    let is_ident_byte = |i: usize| bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_';
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        let start = i;
+        if rest.starts_with(b"//") {
+            i += rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+            continue;
+        }
+        if rest.starts_with(b"/*") {
+            let mut depth = 0;
+            while i < bytes.len() {
+                if bytes[i..].starts_with(b"/*") {
+                    depth += 1;
+                    i += 2;
+                } else if bytes[i..].starts_with(b"*/") {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            continue;
+        }
+        // raw strings such as `r"..."`, `r#"..."#` and `br"..."`
+        let raw_prefix = i == 0
+            || !is_ident_byte(i - 1)
+            || (bytes[i - 1] == b'b' && (i == 1 || !is_ident_byte(i - 2)));
+        if rest[0] == b'r' && raw_prefix {
+            let hashes = rest[1..].iter().take_while(|b| **b == b'#').count();
+            if rest.get(1 + hashes) == Some(&b'"') {
+                let closing = format!("\"{}", "#".repeat(hashes)).into_bytes();
+                let body = i + 2 + hashes;
+                i = bytes[body..]
+                    .windows(closing.len())
+                    .position(|window| window == closing.as_slice())
+                    .map(|pos| body + pos + closing.len())
+                    .unwrap_or(bytes.len());
+                literals[start..i].iter_mut().for_each(|pos| *pos = true);
+                continue;
+            }
+        }
+        match rest[0] {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += match bytes[i] {
+                        b'\\' => 2,
+                        _ => 1,
+                    };
+                }
+                i = min(i + 1, bytes.len());
+                literals[start..i].iter_mut().for_each(|pos| *pos = true);
+            }
+            b'\'' => {
+                // distinguish character literals such as `'"'` and `'\''` from lifetimes
+                let len = match rest.get(1) {
+                    Some(b'\\') => rest
+                        .get(3..)
+                        .and_then(|tail| tail.iter().position(|b| *b == b'\''))
+                        .map(|pos| pos + 4),
+                    Some(_) => source[i + 1..]
+                        .chars()
+                        .next()
+                        .map(|c| 1 + c.len_utf8())
+                        .filter(|&len| rest.get(len) == Some(&b'\''))
+                        .map(|len| len + 1),
+                    None => None,
+                };
+                i += len.unwrap_or(1);
+                if len.is_some() {
+                    literals[start..i].iter_mut().for_each(|pos| *pos = true);
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    literals
+}
+
+/// Finds all non-overlapping matches of `regex` within `value` that don't start inside one of
+/// the `literals` found by [`literal_positions`].
+fn find_outside_literals<'a>(
+    regex: &Regex,
+    value: &'a str,
+    literals: &[bool],
+) -> Vec<regex::Match<'a>> {
+    let mut matches = Vec::new();
+    let mut pos = 0;
+    while let Some(m) = regex.find_at(value, pos) {
+        if literals[m.start()] {
+            // skip ahead by one character and look for a match outside of the literal
+            pos = m.start() + value[m.start()..].chars().next().map_or(1, char::len_utf8);
+            continue;
+        }
+        pos = match m.end() > m.start() {
+            true => m.end(),
+            false => m.end() + 1,
+        };
+        matches.push(m);
+        if pos > value.len() {
+            break;
+        }
+    }
+    matches
+}
+
+/// Responsible for retrieving the "contents" of an item, used by `#[docify::export_contents]`
+fn get_content_tokens(item: &Item) -> TokenStream2 {
+    match item {
+        // Item::Const(item_const) => item_const.to_token_stream(),
+        // Item::Enum(item_enum) => item_enum.to_token_stream(),
+        // Item::ExternCrate(item_extern) => item_extern.to_token_stream(),
+        Item::Fn(item_fn) => {
+            let mut tokens = TokenStream2::new();
+            tokens.extend(item_fn.block.stmts.iter().map(|t| t.to_token_stream()));
+            tokens
+        }
+        Item::ForeignMod(item_mod) => {
+            let mut tokens = TokenStream2::new();
+            tokens.extend(item_mod.items.iter().map(|t| t.to_token_stream()));
+            tokens
+        }
+        Item::Impl(item_impl) => {
+            let mut tokens = TokenStream2::new();
+            tokens.extend(item_impl.items.iter().map(|t| t.to_token_stream()));
+            tokens
+        }
+        // Item::Macro(item_macro) => item_macro.to_token_stream(),
+        Item::Mod(item_mod) => {
+            let Some(content) = &item_mod.content else {
+                return item_mod.to_token_stream();
+            };
+            let mut tokens = TokenStream2::new();
+            tokens.extend(content.1.iter().map(|t| t.to_token_stream()));
+            tokens
+        }
+        // Item::Static(item_static) => item_static.to_token_stream(),
+        // Item::Struct(item_struct) => item_struct.to_token_stream(),
+        Item::Trait(item_trait) => {
+            let mut tokens = TokenStream2::new();
+            tokens.extend(item_trait.items.iter().map(|t| t.to_token_stream()));
+            tokens
+        }
+        Item::TraitAlias(item_trait_alias) => item_trait_alias.to_token_stream(),
+        // Item::Type(item_type) => item_type.to_token_stream(),
+        // Item::Union(item_union) => item_union.to_token_stream(),
+        // Item::Use(item_use) => item_use.to_token_stream(),
+        // Item::Verbatim(item_verbatim) => item_verbatim.to_token_stream(),
+        _ => item.to_token_stream(),
+    }
+}
+
+/// Extends the (exclusive) `end` position within `source` to include a comment that trails
+/// it on the same line, such as `} // end of example`, if there is one.
+fn trailing_comment_position<S: AsRef<str>>(source: S, end: usize) -> usize {
+    let source = source.as_ref();
+    let line = source[end..].lines().next().unwrap_or("");
+    let trimmed = line.trim();
+    if trimmed.starts_with("//") || (trimmed.starts_with("/*") && trimmed.ends_with("*/")) {
+        return end + line.trim_end().len();
+    }
+    end
+}
+
+/// Returns the 1-based line number of the specified byte position within `source`.
+fn line_number<S: AsRef<str>>(source: S, pos: usize) -> usize {
+    source.as_ref()[..pos].matches('\n').count() + 1
+}
+
+/// Finds and returns the specified [`Item`] within a source text string and returns the exact
+/// source code of that item, without any formatting changes.
+fn source_excerpt<'a, T: ToTokens>(
+    file: &'a SourceFile,
+    item: &'a T,
+    style: ResultStyle,
+) -> Result<String> {
+    let range = source_excerpt_range(file, item, style)?;
+    Ok(file.source[range]
+        .lines()
+        .filter_map(strip_docify_attributes)
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+/// Returns the source code of the specified file with each of the `hidden` items removed, along
+/// with their `#[docify::hide]` attributes and doc comments.
+fn remove_hidden_items(file: &SourceFile, hidden: &[TokenStream2]) -> Result<String> {
+    let source = &file.source;
+    let ranges = hidden
+        .iter()
+        .map(|item| {
+            let range = source_excerpt_range(file, item, ResultStyle::Export)?;
+            Ok(removal_range(source, range))
+        })
+        .collect::<Result<Vec<Range<usize>>>>()?;
+    let mut code = remove_ranges(source, 0..source.len(), ranges);
+    // don't leave behind a blank line where a hidden item at the end of the file used to be
+    let trimmed_len = code.trim_end().len();
+    code.truncate(trimmed_len);
+    if source.ends_with('\n') {
+        code.push('\n');
+    }
+    Ok(code)
+}
+
+/// Extends the specified range of an item in `source` to also cover the lines of the docify
+/// attributes and doc comments above it, the line break following it, and the blank line
+/// separating it from the next (or, at the end of a block, previous) item, unless that is the
+/// only thing separating the surrounding items, so that removing the item leaves no trace of
+/// it.
+fn removal_range(source: &str, range: Range<usize>) -> Range<usize> {
+    let mut start = range.start;
+    for line in source[..range.start].lines().rev() {
+        let trimmed = line.trim();
+        if !trimmed.starts_with("///") && strip_docify_attributes(line).is_some() {
+            break;
+        }
+        start = line_start_position(source, start.saturating_sub(1));
+    }
+    let mut end = match source[range.end..].starts_with('\n') {
+        true => range.end + 1,
+        false => range.end,
+    };
+    let previous = source[..start].trim_end_matches([' ', '\t']);
+    let separated =
+        previous.is_empty() || previous.ends_with("\n\n") || previous.trim_end().ends_with('{');
+    if separated {
+        if let Some(next_line) = source[end..].split_inclusive('\n').next() {
+            if next_line.trim().is_empty() {
+                end += next_line.len();
+            }
+        }
+    }
+    // the last item of a block takes the blank line separating it from the previous item along
+    // with it instead
+    if source[end..].trim_start().starts_with('}') && previous.ends_with("\n\n") {
+        start = previous.len() - 1;
+    }
+    start..end
+}
+
+/// Returns the part of `source` within `within`, leaving out each of the (possibly overlapping)
+/// `ranges`.
+fn remove_ranges(source: &str, within: Range<usize>, mut ranges: Vec<Range<usize>>) -> String {
+    ranges.sort_by_key(|range| range.start);
+    let mut code = String::new();
+    let mut cursor = within.start;
+    for range in ranges {
+        if range.start < cursor || range.end > within.end {
+            continue;
+        }
+        code.push_str(&source[cursor..range.start]);
+        cursor = range.end;
+    }
+    code.push_str(&source[cursor..within.end]);
+    code
+}
+
+/// Returns the items nested inside the specified module (and inside its public submodules)
+/// that aren't visible outside of it, i.e. those without any `pub` visibility, for the
+/// `pub_only` option. Items without a visibility of their own, such as `impl` blocks, are
+/// considered public.
+fn private_items(item_mod: &ItemMod) -> Vec<TokenStream2> {
+    let Some((_, items)) = &item_mod.content else {
+        return Vec::new();
+    };
+    let mut private = Vec::new();
+    for item in items {
+        let vis = match item {
+            Item::Const(item) => Some(&item.vis),
+            Item::Enum(item) => Some(&item.vis),
+            Item::ExternCrate(item) => Some(&item.vis),
+            Item::Fn(item) => Some(&item.vis),
+            Item::Mod(item) => Some(&item.vis),
+            Item::Static(item) => Some(&item.vis),
+            Item::Struct(item) => Some(&item.vis),
+            Item::Trait(item) => Some(&item.vis),
+            Item::TraitAlias(item) => Some(&item.vis),
+            Item::Type(item) => Some(&item.vis),
+            Item::Union(item) => Some(&item.vis),
+            Item::Use(item) => Some(&item.vis),
+            _ => None,
+        };
+        match (vis, item) {
+            (Some(Visibility::Inherited), _) => private.push(item.to_token_stream()),
+            (_, Item::Mod(item_mod)) => private.extend(private_items(item_mod)),
+            _ => {}
+        }
+    }
+    private
+}
+
+/// Like [`source_excerpt`], but leaves out the [`private_items`] of the specified module, for
+/// the `pub_only` option.
+fn public_excerpt(file: &SourceFile, item: &TokenStream2, style: ResultStyle) -> Result<String> {
+    let Ok(Item::Mod(item_mod)) = parse2::<Item>(item.clone()) else {
+        return Err(Error::new_spanned(
+            item,
+            "The `pub_only` option can only be used with inline modules.",
+        ));
+    };
+    let range = source_excerpt_range(file, item, style)?;
+    // only look for the private items inside of the module, rather than any identical items
+    // that come before it
+    let from = find_compressed(file, item, item.span())?.start;
+    let ranges = private_items(&item_mod)
+        .iter()
+        .map(|private| {
+            let private_range =
+                source_excerpt_range_from(file, private, ResultStyle::Export, from)?;
+            Ok(removal_range(&file.source, private_range))
+        })
+        .collect::<Result<Vec<Range<usize>>>>()?;
+    Ok(remove_ranges(&file.source, range, ranges)
+        .lines()
+        .filter_map(strip_docify_attributes)
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+/// Returns the initializer expression of the specified `const` or `static` item (including
+/// associated consts), or `None` if it is any other kind of item.
+fn const_expr(item: &TokenStream2) -> Option<Expr> {
+    if let Ok(ItemConst { expr, .. }) = parse2::<ItemConst>(item.clone()) {
+        Some(*expr)
+    } else if let Ok(ItemStatic { expr, .. }) = parse2::<ItemStatic>(item.clone()) {
+        Some(*expr)
+    } else if let Ok(ImplItemConst { expr, .. }) = parse2::<ImplItemConst>(item.clone()) {
+        Some(expr)
+    } else {
+        None
+    }
+}
+
+/// Returns the exact source code of the initializer expression of the specified `const` or
+/// `static` item or `let` statement, leaving out the declaration itself. Lines after the first
+/// are dedented by the indentation of the line the expression starts on.
+fn value_excerpt(file: &SourceFile, item: &TokenStream2) -> Result<String> {
+    let local_init = || match parse2::<Stmt>(item.clone()) {
+        Ok(Stmt::Local(local)) => local.init.map(|init| *init.expr),
+        _ => None,
+    };
+    let Some(expr) = const_expr(item).or_else(local_init) else {
+        return Err(Error::new(
+            item.span(),
+            "The `value_only` option can only be used with `const` and `static` items and \
+            `let` statements with an initializer.",
+        ));
+    };
+    let found_item = find_compressed(file, item, item.span())?;
+    let found = find_compressed_from(file, &expr.to_token_stream(), found_item.start, item.span())?;
+    let (start_c, end_c) = original_chars(file, found.start, found.end - 1, item.span())?;
+    let source = &file.source;
+    let start_pos = start_c.original_pos;
+    let end_pos = end_c.original_pos + end_c.char.len_utf8();
+    let line_start = line_start_position(source, start_pos);
+    let before = &source[line_start..start_pos];
+    let indent = &before[..before.len() - before.trim_start().len()];
+    let mut lines = source[start_pos..end_pos].lines();
+    let mut excerpt: Vec<&str> = lines.next().into_iter().collect();
+    excerpt.extend(lines.map(|line| line.strip_prefix(indent).unwrap_or(line)));
+    Ok(excerpt.join("\n"))
+}
+
+/// Appends the value of the specified `const` or `static` item, as evaluated by
+/// [`eval_int_expr`], to its source code as a `// = value` comment.
+fn annotate_const_value(item: &TokenStream2, excerpt: String) -> Result<String> {
+    let Some(expr) = const_expr(item) else {
+        return Err(Error::new(
+            item.span(),
+            "The `eval_const` option can only be used with `const` and `static` items.",
+        ));
+    };
+    let Some(value) = eval_int_expr(&expr) else {
+        return Err(Error::new(
+            item.span(),
+            "The `eval_const` option can only evaluate integer literals combined with \
+            arithmetic, bitwise, and shift operators.",
+        ));
+    };
+    // don't tack the value onto a trailing comment
+    let separator = match excerpt
+        .lines()
+        .last()
+        .is_some_and(|line| line.contains("//"))
+    {
+        true => "\n",
+        false => " ",
+    };
+    Ok(format!("{}{}// = {}", excerpt, separator, value))
+}
+
+/// Evaluates an expression made up only of integer literals, arithmetic, bitwise and shift
+/// operators, parentheses, and casts (which are ignored), returning `None` for anything else or
+/// if the result overflows.
+fn eval_int_expr(expr: &Expr) -> Option<i128> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => lit.base10_parse::<i128>().ok(),
+        Expr::Paren(paren) => eval_int_expr(&paren.expr),
+        Expr::Group(group) => eval_int_expr(&group.expr),
+        Expr::Cast(cast) => eval_int_expr(&cast.expr),
+        Expr::Unary(unary) => {
+            let value = eval_int_expr(&unary.expr)?;
+            // `!` is not supported, since its result depends on the type of the value
+            match unary.op {
+                UnOp::Neg(_) => value.checked_neg(),
+                _ => None,
+            }
+        }
+        Expr::Binary(binary) => {
+            let left = eval_int_expr(&binary.left)?;
+            let right = eval_int_expr(&binary.right)?;
+            match binary.op {
+                BinOp::Add(_) => left.checked_add(right),
+                BinOp::Sub(_) => left.checked_sub(right),
+                BinOp::Mul(_) => left.checked_mul(right),
+                BinOp::Div(_) => left.checked_div(right),
+                BinOp::Rem(_) => left.checked_rem(right),
+                BinOp::BitAnd(_) => Some(left & right),
+                BinOp::BitOr(_) => Some(left | right),
+                BinOp::BitXor(_) => Some(left ^ right),
+                BinOp::Shl(_) => left.checked_shl(u32::try_from(right).ok()?),
+                BinOp::Shr(_) => left.checked_shr(u32::try_from(right).ok()?),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the contiguous comment lines immediately preceding the line starting at byte position
+/// `start` within `source`, skipping over lines consisting only of docify export attributes,
+/// or an empty string if there are none.
+fn preamble_excerpt(source: &str, start: usize) -> String {
+    let mut preamble: Vec<&str> = Vec::new();
+    let mut in_block_comment = false;
+    for line in source[..start].lines().rev() {
+        let trimmed = line.trim();
+        if in_block_comment {
+            preamble.push(line);
+            in_block_comment = !trimmed.starts_with("/*");
+        } else if trimmed.starts_with("//") {
+            preamble.push(line);
+        } else if trimmed.ends_with("*/") {
+            preamble.push(line);
+            in_block_comment = !trimmed.starts_with("/*");
+        } else if trimmed.is_empty() || strip_docify_attributes(line).is_some() {
+            break;
+        }
+    }
+    preamble.reverse();
+    preamble.join("\n")
+}
+
+/// Removes any docify export attributes (including those of nested items) from the specified
+/// line of source code, returning `None` if nothing but whitespace remains. Comment lines are
+/// left as-is, since they may legitimately mention `#[docify::export]`.
+fn strip_docify_attributes(line: &str) -> Option<String> {
+    if line.trim().starts_with("//") || !DOCIFY_ATTRIBUTES.is_match(line) {
+        return Some(line.to_string());
+    }
+    let mut stripped = String::new();
+    let mut cursor = 0;
+    for m in DOCIFY_ATTRIBUTES.find_iter(line) {
+        stripped.push_str(&line[cursor..m.start()]);
+        // also remove whitespace separating the attribute from whatever follows it
+        cursor = m.end() + (line[m.end()..].len() - line[m.end()..].trim_start().len());
+    }
+    stripped.push_str(&line[cursor..]);
+    match stripped.trim().is_empty() {
+        true => None,
+        false => Some(stripped.trim_end().to_string()),
+    }
+}
+
+/// Finds the specified [`Item`] within a source text string and returns the byte range of the
+/// exact source code of that item, starting at the beginning of its first line. If span
+/// locations are stabilized, this can be removed along with most of the [`CompressedString`]
+/// machinery.
+fn source_excerpt_range<'a, T: ToTokens>(
+    file: &'a SourceFile,
+    item: &'a T,
+    style: ResultStyle,
+) -> Result<Range<usize>> {
+    source_excerpt_range_from(file, item, style, 0)
+}
+
+/// Like [`source_excerpt_range`], but only finds the item at or after the character index
+/// `from` of the [`CompressedString`] of the file, see [`find_compressed_from`].
+fn source_excerpt_range_from<'a, T: ToTokens>(
+    file: &'a SourceFile,
+    item: &'a T,
+    style: ResultStyle,
+    from: usize,
+) -> Result<Range<usize>> {
+    let source = &file.source;
+    let item_tokens = match style {
+        ResultStyle::Export | ResultStyle::Variant => item.to_token_stream(),
+        // statements have no notion of content, so they are exported as-is
+        ResultStyle::ExportContent => match parse2::<Item>(item.to_token_stream()) {
+            Ok(item) => get_content_tokens(&item),
+            Err(_) => item.to_token_stream(),
+        },
+    };
+    let found = find_compressed_from(file, &item_tokens, from, item.span())?;
+    let (start_c, end_c) = original_chars(file, found.start, found.end - 1, item.span())?;
+    let start_pos = line_start_position(source, start_c.original_pos);
+    let end_pos = end_c.original_pos + end_c.char.len_utf8();
+    let end_pos = trailing_comment_position(source, min(end_pos, source.len()));
+    Ok(start_pos..end_pos)
+}
+
+/// Finds the specified tokens within the [`CompressedString`] of the specified source file,
+/// returning the range of character indices they occupy within it.
+fn find_compressed(file: &SourceFile, tokens: &TokenStream2, span: Span) -> Result<Range<usize>> {
+    find_compressed_from(file, tokens, 0, span)
+}
+
+/// Like [`find_compressed`], but only finds occurrences starting at or after the character
+/// index `from` of the [`CompressedString`].
+fn find_compressed_from(
+    file: &SourceFile,
+    tokens: &TokenStream2,
+    from: usize,
+    span: Span,
+) -> Result<Range<usize>> {
+    // note: can't rely on span locations because this requires nightly and/or is otherwise
+    // bugged
+    let (_, compressed_source_string) = file.compressed();
+    let compressed_item_string = CompressedString::from(&tokens.to_string()).to_string();
+    let from = compressed_source_string
+        .char_indices()
+        .nth(from)
+        .map(|(i, _)| i)
+        .unwrap_or(compressed_source_string.len());
+    let Some(found_start) = compressed_source_string[from..]
+        .find(compressed_item_string.as_str())
+        .map(|found_start| from + found_start)
+    else {
+        return Err(Error::new(
+            span,
+            "You have found a bug in docify! Please submit a new GitHub issue at \
+            https://github.com/sam0x17/docify/issues/new?title=%60source_excerpt\
+            %60%3A%20can%27t%20find%20item%20in%20source with a sample of the item \
+            you are trying to embed.",
+        ));
+    };
+    // `found_start` is a byte offset, whereas `chars` is indexed by character
+    let found_start = compressed_source_string[..found_start].chars().count();
+    Ok(found_start..found_start + compressed_item_string.chars().count())
+}
+
+/// Maps the characters at the specified indices of the [`CompressedString`] of the specified
+/// source file back to their [`OffsetChar`]s in the original source.
+fn original_chars(
+    file: &SourceFile,
+    start: usize,
+    end: usize,
+    span: Span,
+) -> Result<(OffsetChar, OffsetChar)> {
+    let (compressed_source, _) = file.compressed();
+    match (
+        compressed_source.chars.get(&start),
+        compressed_source.chars.get(&end),
+    ) {
+        (Some(start_c), Some(end_c)) => Ok((*start_c, *end_c)),
+        _ => Err(Error::new(
+            span,
+            "Could not map the embedded item back to its original position in the source.",
+        )),
+    }
+}
+
+/// Returns the tokens making up the signature of the specified function item, i.e. everything
+/// up to (but excluding) its body, or `None` if the item is not a function with a body.
+fn fn_signature_tokens(item: &TokenStream2) -> Option<TokenStream2> {
+    if let Ok(ItemFn {
+        attrs, vis, sig, ..
+    }) = parse2::<ItemFn>(item.clone())
+    {
+        return Some(quote!(#(#attrs)* #vis #sig));
+    }
+    if let Ok(ImplItemFn {
+        attrs,
+        vis,
+        defaultness,
+        sig,
+        ..
+    }) = parse2::<ImplItemFn>(item.clone())
+    {
+        return Some(quote!(#(#attrs)* #vis #defaultness #sig));
+    }
+    match parse2::<TraitItemFn>(item.clone()) {
+        Ok(TraitItemFn {
+            attrs,
+            sig,
+            default: Some(_),
+            ..
+        }) => Some(quote!(#(#attrs)* #sig)),
+        _ => None,
+    }
+}
+
+/// Like [`source_excerpt`], but only returns the exact source code of the signature of the
+/// specified function item, followed by a `;` in place of its body.
+fn signature_excerpt(file: &SourceFile, item: &TokenStream2) -> Result<String> {
+    let Some(signature) = fn_signature_tokens(item) else {
+        return Err(Error::new(
+            item.span(),
+            "The `signature_only` option can only be used with functions that have a body.",
+        ));
+    };
+    let found = find_compressed(file, item, item.span())?;
+    let signature_len = CompressedString::from(&signature.to_string())
+        .to_string()
+        .chars()
+        .count();
+    let (start_c, end_c) = original_chars(
+        file,
+        found.start,
+        found.start + signature_len - 1,
+        item.span(),
+    )?;
+    let start_pos = line_start_position(&file.source, start_c.original_pos);
+    let end_pos = end_c.original_pos + end_c.char.len_utf8();
+    let excerpt = file.source[start_pos..end_pos]
+        .lines()
+        .filter_map(strip_docify_attributes)
+        .collect::<Vec<String>>()
+        .join("\n");
+    // drop the trailing comma of a multi-line `where` clause, i.e. `T: Debug,;`
+    let mut excerpt = excerpt.trim_end_matches(',').to_string();
+    excerpt.push(';');
+    Ok(excerpt)
+}
+
+/// Like [`source_excerpt`], but replaces the default body of each method of the specified trait
+/// item with a `;`, so that only the signatures of its methods remain.
+fn trait_signatures_excerpt(file: &SourceFile, item: &TokenStream2) -> Result<String> {
+    let Ok(item_trait) = parse2::<ItemTrait>(item.clone()) else {
+        return Err(Error::new(
+            item.span(),
+            "The `signatures_only` option can only be used with trait definitions.",
+        ));
+    };
+    let range = source_excerpt_range(file, item, ResultStyle::Export)?;
+    let found = find_compressed(file, item, item.span())?;
+    let mut excerpt = String::new();
+    let mut cursor = range.start;
+    for trait_item in &item_trait.items {
+        let TraitItem::Fn(trait_item_fn) = trait_item else {
+            continue;
+        };
+        let tokens = trait_item_fn.to_token_stream();
+        let Some(signature) = fn_signature_tokens(&tokens) else {
+            continue;
+        };
+        let method = find_compressed_from(file, &tokens, found.start, item.span())?;
+        let signature_len = CompressedString::from(&signature.to_string())
+            .to_string()
+            .chars()
+            .count();
+        let (signature_end, body_end) = original_chars(
+            file,
+            method.start + signature_len - 1,
+            method.end - 1,
+            item.span(),
+        )?;
+        let signature_end = signature_end.original_pos + signature_end.char.len_utf8();
+        // drop the trailing comma of a multi-line `where` clause, i.e. `T: Debug,;`
+        excerpt.push_str(file.source[cursor..signature_end].trim_end_matches(','));
+        excerpt.push(';');
+        cursor = body_end.original_pos + body_end.char.len_utf8();
+    }
+    excerpt.push_str(&file.source[cursor..range.end]);
+    Ok(excerpt
+        .lines()
+        .filter_map(strip_docify_attributes)
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+/// The contents of a source file read by `docify::embed!(..)`, along with its lazily computed
+/// [`CompressedString`]. Parsed [`File`]s are deliberately not kept here: their tokens hold
+/// `proc_macro` handles that become invalid as soon as the macro invocation that created them
+/// returns, so only data derived purely from the text can be reused by later invocations.
+struct SourceFile {
+    source: String,
+    compressed: OnceCell<(CompressedString, String)>,
+}
+
+impl SourceFile {
+    /// Creates a [`SourceFile`] from the specified source code, normalizing CRLF line endings
+    /// to `\n` so that examples are identical regardless of the platform they were checked out
+    /// on.
+    fn new(source: String) -> SourceFile {
+        let source = match source.contains('\r') {
+            true => source.replace("\r\n", "\n"),
+            false => source,
+        };
+        SourceFile {
+            source,
+            compressed: OnceCell::new(),
+        }
+    }
+
+    /// Returns the [`CompressedString`] of this file, along with its string representation.
+    fn compressed(&self) -> &(CompressedString, String) {
+        self.compressed.get_or_init(|| {
+            let compressed = CompressedString::from(&self.source);
+            let compressed_string = compressed.to_string();
+            (compressed, compressed_string)
+        })
+    }
+}
+
+thread_local! {
+    /// Source files read so far by this process, keyed by canonicalized path so the same file
+    /// is only read once, however it is referred to. Hosts such as rust-analyzer keep expanding
+    /// macros in the same process while files are edited, so each entry also records the
+    /// [`SourceStamp`] of the file it was read from and is only reused while that still matches.
+    static SOURCE_FILES: RefCell<HashMap<PathBuf, (SourceStamp, Rc<SourceFile>)>> =
+        RefCell::new(HashMap::new());
+}
+
+/// The modification time and length of a source file, used to tell whether a cached copy of
+/// it in [`SOURCE_FILES`] is stale. `None` if the platform doesn't record modification times,
+/// in which case the file is never reused.
+type SourceStamp = Option<(SystemTime, u64)>;
+
+/// The reasons a source file could not be loaded by [`load_source_file`].
+#[derive(Debug)]
+enum SourceFileError {
+    /// The file could not be read at all, i.e. because it doesn't exist.
+    Io(std::io::Error),
+    /// The file was read, but is not valid UTF-8, starting at the specified byte offset.
+    InvalidUtf8 { offset: usize },
+}
+
+impl SourceFileError {
+    /// Describes this error, along with how it might be fixed, for the file at `path`, which
+    /// was resolved against the directory of the `path_env` option, if specified.
+    fn message(&self, path: &Path, path_env: Option<&(String, PathBuf)>) -> String {
+        match self {
+            SourceFileError::Io(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                // the current directory of the compiler is rarely what paths are relative to,
+                // so show exactly where docify looked
+                let absolute = match path.is_absolute() {
+                    true => path.to_path_buf(),
+                    false => std::env::current_dir().unwrap_or_default().join(path),
+                };
+                let root = match (path_env, std::env::var("CARGO_MANIFEST_DIR")) {
+                    (Some((var, dir)), _) => format!(
+                        "Relative paths are resolved against {} ('{}'), as specified by the \
+                        `path_env` option.",
+                        var,
+                        dir.display()
+                    ),
+                    (None, Ok(dir)) => format!(
+                        "Relative paths are resolved against CARGO_MANIFEST_DIR ('{}'), or the \
+                        workspace root if `workspace_relative` is specified.",
+                        dir
+                    ),
+                    (None, Err(_)) => String::from(
+                        "CARGO_MANIFEST_DIR is not set, so relative paths are resolved against \
+                        the current directory.",
+                    ),
+                };
+                format!(
+                    "Could not read the specified path '{}' because it does not exist. {}",
+                    absolute.display(),
+                    root,
+                )
+            }
+            SourceFileError::Io(err) if err.kind() == std::io::ErrorKind::PermissionDenied => {
+                format!(
+                    "Could not read the specified path '{}' because permission was denied.",
+                    path.display(),
+                )
+            }
+            SourceFileError::Io(err) => format!(
+                "Could not read the specified path '{}': {}.",
+                path.display(),
+                err,
+            ),
+            SourceFileError::InvalidUtf8 { offset } => format!(
+                "The specified path '{}' is not valid UTF-8 (invalid byte at offset {}). Only \
+                UTF-8 encoded text files can be embedded, so make sure this is not a binary \
+                file and re-encode it if it uses another encoding such as latin-1.",
+                path.display(),
+                offset,
+            ),
+        }
+    }
+}
+
+/// Reads the source file at the specified path, or returns the cached copy if it has already
+/// been read and hasn't been modified since.
+fn load_source_file(path: &Path) -> std::result::Result<Rc<SourceFile>, SourceFileError> {
+    let path = fs::canonicalize(path).map_err(SourceFileError::Io)?;
+    let metadata = fs::metadata(&path).map_err(SourceFileError::Io)?;
+    let stamp = metadata
+        .modified()
+        .ok()
+        .map(|modified| (modified, metadata.len()));
+    let cached = SOURCE_FILES.with(|files| match files.borrow().get(&path) {
+        Some((cached_stamp, file)) if stamp.is_some() && *cached_stamp == stamp => {
+            Some(file.clone())
+        }
+        _ => None,
+    });
+    if let Some(file) = cached {
+        return Ok(file);
+    }
+    let bytes = fs::read(&path).map_err(SourceFileError::Io)?;
+    let source = String::from_utf8(bytes).map_err(|err| SourceFileError::InvalidUtf8 {
+        offset: err.utf8_error().valid_up_to(),
+    })?;
+    let file = Rc::new(SourceFile::new(source));
+    SOURCE_FILES.with(|files| files.borrow_mut().insert(path, (stamp, file.clone())));
+    Ok(file)
+}
+
+/// Parses the specified source code string as a rust source [`File`].
+///
+/// If the source code fails to parse, the line and column in `file_path` at which it did so is
+/// included in the error, since otherwise the error would only point at the macro invocation.
+fn parse_source_file(source_code: &str, file_path: &Path) -> Result<File> {
+    let parsed = match source_code.parse::<TokenStream2>() {
+        Ok(parsed) => parsed,
+        Err(err) => return Err(located_parse_error(source_code, file_path, err.into())),
+    };
+    parse2::<File>(parsed).map_err(|err| located_parse_error(source_code, file_path, err))
+}
+
+/// Adds the line and column within `file_path` at which `source_code` fails to parse to the
+/// specified parse error. Tokens parsed from a string inside a proc macro are all given the
+/// span of the macro invocation, so the location is found by [`parse_error_position`] instead.
+fn located_parse_error(source_code: &str, file_path: &Path, err: Error) -> Error {
+    match parse_error_position(source_code) {
+        Some(pos) => {
+            let line_start = line_start_position(source_code, pos);
+            Error::new(
+                err.span(),
+                format!(
+                    "{} (at line {}, column {} of '{}')",
+                    err,
+                    line_number(source_code, pos),
+                    source_code[line_start..pos].chars().count() + 1,
+                    file_path.display(),
+                ),
+            )
+        }
+        None => Error::new(
+            err.span(),
+            format!("{} (at the end of '{}')", err, file_path.display()),
+        ),
+    }
+}
+
+/// Returns the byte position within `source_code` of the token at which it fails to parse, or
+/// `None` if it fails at the very end (or doesn't fail at all).
+///
+/// Parsing proceeds from left to right, so once a prefix of the tokens of `source_code` (with
+/// any groups it leaves open closed again) includes the offending token, it fails with the
+/// same error as the whole of `source_code`. The shortest such prefix is found by bisection.
+fn parse_error_position(source_code: &str) -> Option<usize> {
+    let tokens = match lex_tokens(source_code) {
+        Ok(tokens) => tokens,
+        Err(pos) => return Some(pos),
+    };
+    // the source code is parsed within braces so that running out of tokens is reported as an
+    // unexpected end of input, rather than as an error at the end of the whole input
+    let parse_error = |end: usize, closers: &str| {
+        let wrapped = format!("{{{}\n{}\n}}", &source_code[..end], closers);
+        let parse_braced = |input: ParseStream| {
+            let content;
+            braced!(content in input);
+            content.parse::<File>()
+        };
+        match wrapped.parse::<TokenStream2>() {
+            Ok(parsed) => parse_braced.parse2(parsed).err().map(|err| err.to_string()),
+            Err(err) => Some(err.to_string()),
+        }
+    };
+    let prefix_error = |index: usize| {
+        let mut closers = Vec::new();
+        for token in &tokens[..=index] {
+            match token.delimiter {
+                Some(b'(') => closers.push(')'),
+                Some(b'[') => closers.push(']'),
+                Some(b'{') => closers.push('}'),
+                Some(_) => drop(closers.pop()),
+                None => {}
+            }
+        }
+        let closers = closers.iter().rev().collect::<String>();
+        parse_error(tokens[index].range.end, &closers)
+    };
+    let message = parse_error(source_code.len(), "")?;
+    let (mut low, mut high) = (0, tokens.len());
+    while low < high {
+        let mid = (low + high) / 2;
+        match prefix_error(mid).as_ref() == Some(&message) {
+            true => high = mid,
+            false => low = mid + 1,
+        }
+    }
+    match message.starts_with("unexpected end of input") {
+        // the prefix ran out of tokens within a group, which is closed by the next token
+        true => tokens.get(low + 1),
+        false => tokens.get(low),
+    }
+    .map(|token| token.range.start)
+}
+
+/// A token of rust source code found by [`lex_tokens`].
+struct LexedToken {
+    range: Range<usize>,
+    /// The delimiter this token opens or closes a group with, if any, i.e. `b'('` or `b')'`.
+    delimiter: Option<u8>,
+}
+
+/// Splits `source_code` into tokens, finely enough that it can be cut off between any two of
+/// them. Whitespace and comments other than doc comments are skipped, while literals (see
+/// [`literal_positions`]), lifetimes and raw identifiers are kept whole. Returns the position
+/// of the first unbalanced delimiter instead, if there is one.
+fn lex_tokens(source_code: &str) -> std::result::Result<Vec<LexedToken>, usize> {
+    let bytes = source_code.as_bytes();
+    let literals = literal_positions(source_code);
+    let is_ident_byte = |i: usize| {
+        i < bytes.len()
+            && !literals[i]
+            && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || !bytes[i].is_ascii())
+    };
+    let mut tokens = Vec::new();
+    let mut open = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        let start = i;
+        let mut delimiter = None;
+        if literals[i] {
+            i += literals[i..].iter().take_while(|literal| **literal).count();
+            // unterminated literals run to the end of the source code
+            if i == bytes.len() && source_code[start..].parse::<TokenStream2>().is_err() {
+                return Err(start);
+            }
+        } else if rest.starts_with(b"//") {
+            i += rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+            let doc = rest.starts_with(b"//!")
+                || (rest.starts_with(b"///") && !rest.starts_with(b"////"));
+            if !doc {
+                continue;
+            }
+        } else if rest.starts_with(b"/*") {
+            let mut depth = 0;
+            while i < bytes.len() {
+                if bytes[i..].starts_with(b"/*") {
+                    depth += 1;
+                    i += 2;
+                } else if bytes[i..].starts_with(b"*/") {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            if depth > 0 {
+                return Err(start);
+            }
+            let doc = rest.starts_with(b"/*!")
+                || (rest.starts_with(b"/**")
+                    && !rest.starts_with(b"/***")
+                    && !rest.starts_with(b"/**/"));
+            if !doc {
+                continue;
+            }
+        } else if rest[0].is_ascii_whitespace() {
+            i += 1;
+            continue;
+        } else if rest[0] == b'\'' || is_ident_byte(i) {
+            i += match rest {
+                [b'\'', ..] => 1,
+                [b'r', b'#', ..] if is_ident_byte(i + 2) => 2,
+                _ => 0,
+            };
+            while is_ident_byte(i) {
+                i += 1;
+            }
+        } else {
+            delimiter = Some(rest[0]);
+            match rest[0] {
+                b'(' | b'[' | b'{' => open.push(start),
+                b')' | b']' | b'}' => match open.pop().map(|pos| (bytes[pos], rest[0])) {
+                    Some((b'(', b')') | (b'[', b']') | (b'{', b'}')) => {}
+                    _ => return Err(start),
+                },
+                _ => delimiter = None,
+            }
+            i += 1;
+        }
+        tokens.push(LexedToken {
+            range: start..i,
+            delimiter,
+        });
+    }
+    match open.pop() {
+        Some(pos) => Err(pos),
+        None => Ok(tokens),
+    }
+}
+
+/// Issues a compile error if the first argument after the `source_path` of a whole-file embed
+/// is a flag, such as `unique`, and the file exports an item of the same name, since the item
+/// was most likely meant to be embedded instead.
+fn ensure_flag_is_not_export(source_file: &File, args: &EmbedArgs, file_path: &Path) -> Result<()> {
+    let Some(option) = args.options.first() else {
+        return Ok(());
+    };
+    if option.value.is_some() || !args.targets.is_empty() {
+        return Ok(());
+    }
+    let mut visitor = ItemVisitor::new(ItemSearch::Name(option.name.clone()), None);
+    visitor.visit_file(source_file);
+    if visitor.results.is_empty() {
+        return Ok(());
+    }
+    Err(Error::new(
+        option.name.span(),
+        format!(
+            "`{}` is parsed as the `{}` option, which embeds all of '{}', but the file also \
+            exports an item named `{}`. To embed that item instead, refer to it by its raw \
+            identifier, i.e. `r#{}`.",
+            option.name,
+            option.name,
+            file_path.display(),
+            option.name,
+            option.name,
+        ),
+    ))
+}
+
+/// Inner version of [`embed_internal`] that just returns the result as a [`String`].
+fn embed_internal_str(tokens: impl Into<TokenStream2>, lang: MarkdownLanguage) -> Result<String> {
+    let args: EmbedArgs = parse2::<EmbedArgs>(tokens.into())?;
+    embed_with(&args, lang, |options| embed_codes(&args, options, false))
+}
+
+/// Like [`embed_internal_str`], but embeds from the specified in-memory source code rather
+/// than from a file, optionally only embedding the item exported as `ident`. This exercises
+/// the same extraction and formatting as `docify::embed!(..)` without touching the
+/// filesystem, which makes it convenient for testing.
+#[cfg(test)]
+fn embed_str(source: &str, ident: Option<&str>, lang: MarkdownLanguage) -> Result<String> {
+    let args = EmbedArgs {
+        file_path: LitStr::new("<inline>", Span::call_site()),
+        file_paths: Vec::new(),
+        target: ident
+            .map(syn::parse_str::<Ident>)
+            .transpose()?
+            .map(EmbedTarget::Item),
+        targets: Vec::new(),
+        options: Vec::new(),
+    };
+    let file = SourceFile::new(source.to_string());
+    embed_with(&args, lang, |options| {
+        embed_source(
+            &file,
+            Path::new(""),
+            Path::new("<inline>"),
+            &args,
+            options,
+            false,
+        )
+    })
+}
+
+/// Resolves the options of the specified `docify::embed!(..)` arguments, uses `embed` to embed
+/// the code of each example, and then wraps each example in a codeblock.
+fn embed_with(
+    args: &EmbedArgs,
+    lang: MarkdownLanguage,
+    embed: impl FnOnce(&EmbedOptions) -> Result<Vec<String>>,
+) -> Result<String> {
+    let mut options = EmbedOptions::from_options(&args.options)?;
+    // examples that will be compiled as doc tests should at least parse
+    options.check_syntax = matches!(lang, MarkdownLanguage::Blank | MarkdownLanguage::NoRun)
+        && options.lang.is_none()
+        && !options.no_check;
+    if let Some(option) = args
+        .options
+        .iter()
+        .find(|option| option.name == "line_numbers")
+    {
+        if matches!(lang, MarkdownLanguage::Blank | MarkdownLanguage::NoRun) {
+            return Err(Error::new(
+                option.name.span(),
+                "The `line_numbers` option makes examples uncompilable, so it can only be used \
+                with `docify::embed!(..)`.",
+            ));
+        }
+    }
+    if let Some(option) = args.options.iter().find(|option| option.name == "raw") {
+        if matches!(lang, MarkdownLanguage::Blank | MarkdownLanguage::NoRun) {
+            return Err(Error::new(
+                option.name.span(),
+                "The `raw` option omits the codeblock that rustdoc compiles, so it can only be \
+                used with `docify::embed!(..)`.",
+            ));
+        }
+        if let Some(fenced) = args.options.iter().find(|option| {
+            [
+                "fence",
+                "edition",
+                "lang",
+                "title",
+                "title_style",
+                "numbered",
+                "highlight",
+            ]
+            .contains(&option.name.to_string().as_str())
+        }) {
+            return Err(Error::new(
+                fenced.name.span(),
+                format!(
+                    "The `{}` option applies to the codeblock, so it cannot be combined with \
+                    the `raw` option.",
+                    fenced.name
+                ),
+            ));
+        }
+    }
+    if let Some(option) = args
+        .options
+        .iter()
+        .find(|option| option.name == "highlight")
+    {
+        if matches!(lang, MarkdownLanguage::Blank | MarkdownLanguage::NoRun) {
+            return Err(Error::new(
+                option.name.span(),
+                "The `highlight` option renders examples as HTML, which rustdoc doesn't \
+                compile, so it can only be used with `docify::embed!(..)`.",
+            ));
+        }
+        let conflict = args.options.iter().find(|other| {
+            ["fence", "edition", "indent"].contains(&other.name.to_string().as_str())
+                || (other.name == "title_style" && matches!(options.title_style, TitleStyle::Fence))
+        });
+        if let Some(conflict) = conflict {
+            return Err(Error::new(
+                conflict.name.span(),
+                format!(
+                    "The `{}` option cannot be combined with the `highlight` option, which \
+                    renders examples as HTML rather than as a codeblock.",
+                    conflict.name
+                ),
+            ));
+        }
+    }
+    if let (Some(_), Some(option)) = (
+        &options.lang,
+        args.options.iter().find(|option| option.name == "edition"),
+    ) {
+        return Err(Error::new(
+            option.name.span(),
+            "The `edition` option only applies to rust examples, so it cannot be combined with \
+            the `lang` option.",
+        ));
+    }
+    let mut codes = embed(&options)?;
+    if codes.is_empty() {
+        return Ok(String::new());
+    }
+    // the description and metadata lines placed before the code of each example by
+    // `embed_source`, which belong in front of its codeblock instead
+    let header_len = usize::from(options.show_desc) + usize::from(options.emit_metadata);
+    let mut headers: Vec<Vec<String>> = Vec::new();
+    for code in &mut codes {
+        let mut lines = code.splitn(header_len + 1, '\n');
+        headers.push(
+            lines
+                .by_ref()
+                .take(header_len)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect(),
+        );
+        *code = lines.next().unwrap_or_default().to_string();
+    }
+    if let Some(expected) = &options.hash {
+        let found = code_hash(&codes.join("\n\n"));
+        if !found.eq_ignore_ascii_case(expected) {
+            let option = args
+                .options
+                .iter()
+                .find(|option| option.name == "hash")
+                .expect("the `hash` option was specified");
+            return Err(Error::new(
+                option.span(),
+                format!(
+                    "The embedded code has changed since its `hash` was pinned, so the \
+                    surrounding docs may be out of date. Once they have been reviewed, update \
+                    the pinned hash to \"{}\".",
+                    found,
+                ),
+            ));
+        }
+    }
+    if options.raw {
+        let code = codes
+            .iter()
+            .map(|code| code.trim_matches('\n'))
+            .collect::<Vec<&str>>()
+            .join("\n\n");
+        let output = headers
+            .concat()
+            .into_iter()
+            .chain([code])
+            .collect::<Vec<String>>()
+            .join("\n");
+        return Ok(indent_lines(&output, options.indent));
+    }
+    if options.single_fence {
+        codes = vec![codes.join("\n\n")];
+        headers = vec![headers.concat()];
+        if options.wrap_main && syn::parse_str::<File>(&codes[0]).is_err() {
+            codes[0] = wrap_in_main(&codes[0]);
+        }
+    }
+    let mut examples: Vec<String> = Vec::new();
+    for (i, code) in codes.into_iter().enumerate() {
+        if options.numbered {
+            examples.push(format!("**Step {}**\n", i + 1));
+        }
+        examples.extend(headers[i].iter().cloned());
+        let info = match options.fence.as_ref().or(options.lang.as_ref()) {
+            Some(info) => info.as_str(),
+            None => lang.fence_info(),
+        };
+        let info = match &options.edition {
+            Some(edition) if info.is_empty() => format!("edition{}", edition),
+            Some(edition) => format!("{},edition{}", info, edition),
+            None => info.to_string(),
+        };
+        let mut example = into_titled_example(
+            code.as_str(),
+            &info,
+            options.title.as_deref(),
+            options.title_style,
+        );
+        if let Some(highlight) = &options.highlight {
+            example = highlight_example(&example, highlight).ok_or_else(|| {
+                let option = args
+                    .options
+                    .iter()
+                    .find(|option| option.name == "highlight")
+                    .expect("the `highlight` option was specified");
+                Error::new(
+                    option.span(),
+                    format!("No line of the embedded code contains `{}`.", highlight),
+                )
+            })?;
+        }
+        examples.push(example);
+    }
+    Ok(indent_lines(&examples.join("\n"), options.indent))
+}
+
+/// Converts the specified fenced example into an HTML `<pre>` block in which each line
+/// containing the identifier `highlight` is wrapped in `<mark>`, for the `highlight` option.
+/// The language of the codeblock is kept as the class of the block, i.e. `language-rust`.
+/// Returns `None` if no line contains the identifier.
+fn highlight_example(example: &str, highlight: &str) -> Option<String> {
+    let pattern = Regex::new(&format!(r"\b{}\b", regex::escape(highlight))).unwrap();
+    let mut lines = example.lines();
+    let info = lines.next().unwrap_or_default().trim_start_matches('`');
+    let mut code: Vec<&str> = lines.collect();
+    code.pop();
+    if !code.iter().any(|line| pattern.is_match(line)) {
+        return None;
+    }
+    let lang = info.split(',').next().unwrap_or_default();
+    let lang = if lang.is_empty() { "rust" } else { lang };
+    let code = code
+        .into_iter()
+        .map(|line| {
+            let escaped = line
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+            match pattern.is_match(line) {
+                true => format!("<mark>{}</mark>", escaped),
+                false => escaped,
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    Some(format!(
+        "<pre><code class=\"language-{}\">{}\n</code></pre>",
+        lang, code
+    ))
+}
+
+/// Prefixes every line of the specified text with `indent` spaces, for the `indent` option.
+fn indent_lines(text: &str, indent: Option<usize>) -> String {
+    let Some(indent) = indent else {
+        return text.to_string();
+    };
+    let prefix = " ".repeat(indent);
+    text.split('\n')
+        .map(|line| format!("{}{}", prefix, line))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Escapes the characters of the specified plain text that markdown would otherwise interpret,
+/// for the `show_desc` option. Line breaks are replaced with spaces, so the text stays on a
+/// single line.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' | '!' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\r' => (),
+            '\n' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped.trim().to_string()
+}
+
+/// The start of the HTML comment emitted before each example by the `emit_metadata` option.
+const METADATA_PREFIX: &str = "<!-- docify: ";
+
+/// Returns the specified string as a JSON string literal, for the `emit_metadata` option.
+fn json_string(value: &str) -> String {
+    let mut json = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            // so the metadata can never end the HTML comment it is emitted in
+            '>' => json.push_str("\\u003e"),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Returns a hex-encoded 64-bit FNV-1a hash of the specified code, for the `hash` option. Unlike
+/// [`std::hash::DefaultHasher`], this is guaranteed to be stable across rust versions.
+fn code_hash(code: &str) -> String {
+    let hash = code.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Resolves the `source_path` of the specified `docify::embed!(..)` arguments and embeds the
+/// requested part of each resulting file, returning the code of each example, which has yet to
+/// be wrapped in a codeblock. If `allow_missing` is `true`, no examples are returned rather than
+/// issuing a compile error when the requested item can't be found.
+fn embed_codes(
+    args: &EmbedArgs,
+    options: &EmbedOptions,
+    allow_missing: bool,
+) -> Result<Vec<String>> {
+    if !args.file_paths.is_empty() {
+        // the item only has to be found in one of several source paths
+        let mut codes: Vec<String> = Vec::new();
+        for file_path in &args.file_paths {
+            let args = EmbedArgs {
+                file_path: file_path.clone(),
+                file_paths: Vec::new(),
+                ..args.clone()
+            };
+            codes.extend(embed_codes(&args, options, true)?);
+        }
+        if codes.is_empty() && !allow_missing {
+            let paths = args
+                .file_paths
+                .iter()
+                .map(|file_path| format!("'{}'", file_path.value()))
+                .collect::<Vec<String>>();
+            let message = match &args.target {
+                Some(target) => format!(
+                    "Could not find docify export item '{}' in any of {}.",
+                    target.name(),
+                    paths.join(", ")
+                ),
+                None => format!(
+                    "Could not find the requested docify export items in any of {}.",
+                    paths.join(", ")
+                ),
+            };
+            return Err(Error::new(args.file_path.span(), message));
+        }
+        return Ok(codes);
+    }
+    // return blank result if we can't properly resolve the root directory
+    let Some((root, file_paths)) = source_files(args, options)? else {
+        return Ok(Vec::new());
+    };
+    if !args.targets.is_empty() {
+        // each of several bracketed targets is embedded in turn, as if specified on its own
+        let mut codes: Vec<String> = Vec::new();
+        for target in &args.targets {
+            let args = EmbedArgs {
+                target: Some(target.clone()),
+                targets: Vec::new(),
+                ..args.clone()
+            };
+            for file_path in &file_paths {
+                codes.extend(embed_file(&root, file_path, &args, options, allow_missing)?);
+            }
+        }
+        return Ok(codes);
+    }
+    let mut codes: Vec<String> = Vec::new();
+    for file_path in file_paths {
+        codes.extend(embed_file(&root, &file_path, args, options, allow_missing)?);
+    }
+    Ok(codes)
+}
+
+/// Resolves the root directory and the source files referred to by the specified
+/// [`EmbedArgs`], downloading the source first if it is a URL. Returns `None` if the root
+/// directory can't be resolved.
+fn source_files(
+    args: &EmbedArgs,
+    options: &EmbedOptions,
+) -> Result<Option<(PathBuf, Vec<PathBuf>)>> {
+    let root = match (&options.path_env, options.workspace_relative) {
+        (Some((_, dir)), _) => Some(dir.clone()),
+        (None, true) => workspace_root(),
+        (None, false) => manifest_dir(),
+    };
+    let Some(root) = root else {
+        return Ok(None);
+    };
+    let file_paths = match is_remote_url(args.file_path.value()) {
+        true => vec![fetch_remote_source(
+            &args.file_path,
+            options.sha256.as_deref(),
+        )?],
+        false => {
+            if let Some(option) = args.options.iter().find(|option| option.name == "sha256") {
+                return Err(Error::new(
+                    option.name.span(),
+                    "The `sha256` option can only be used when embedding from a URL.",
+                ));
+            }
+            resolve_source_paths(&root, &args.file_path)?
+        }
+    };
+    Ok(Some((root, file_paths)))
+}
+
+/// Returns `true` if the specified `source_path` is an `http://` or `https://` URL.
+fn is_remote_url<S: AsRef<str>>(path: S) -> bool {
+    let path = path.as_ref();
+    path.starts_with("https://") || path.starts_with("http://")
+}
+
+/// Stand-in for the `remote` feature's [`fetch_remote_source`], which issues a compile error
+/// explaining how to enable embedding from URLs.
+#[cfg(not(feature = "remote"))]
+fn fetch_remote_source(source_path: &LitStr, _sha256: Option<&str>) -> Result<PathBuf> {
+    Err(Error::new(
+        source_path.span(),
+        "Embedding from a URL requires the `remote` feature of docify to be enabled.",
+    ))
+}
+
+/// The maximum size of a file that will be downloaded by [`fetch_remote_source`].
+#[cfg(feature = "remote")]
+const REMOTE_SIZE_LIMIT: u64 = 10 * 1024 * 1024;
+
+/// How long [`fetch_remote_source`] waits to connect to a server before giving up.
+#[cfg(feature = "remote")]
+const REMOTE_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How long [`fetch_remote_source`] waits for each read from a server before giving up.
+#[cfg(feature = "remote")]
+const REMOTE_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Returns the lowercase hex-encoded SHA-256 digest of the specified bytes.
+#[cfg(feature = "remote")]
+fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// The directory in which [`fetch_remote_source`] caches downloaded files.
+#[cfg(feature = "remote")]
+struct RemoteCache {
+    dir: PathBuf,
+    /// Whether cached files can be reused without a pinned checksum, which is only the case
+    /// inside `OUT_DIR`, since anyone could have written them to the shared temporary directory.
+    trusted: bool,
+}
+
+#[cfg(feature = "remote")]
+impl RemoteCache {
+    /// The cache inside `OUT_DIR` if the crate being compiled has a build script, otherwise
+    /// inside the system's temporary directory.
+    fn new() -> RemoteCache {
+        match std::env::var_os("OUT_DIR") {
+            Some(out_dir) => RemoteCache {
+                dir: PathBuf::from(out_dir).join("docify-remote"),
+                trusted: true,
+            },
+            None => RemoteCache {
+                dir: std::env::temp_dir().join("docify-remote"),
+                trusted: false,
+            },
+        }
+    }
+
+    /// The path at which the contents of `url` are cached.
+    fn path(&self, url: &str) -> PathBuf {
+        self.dir.join(sha256_hex(url.as_bytes()))
+    }
+
+    /// Returns the path of the previously downloaded contents of `url`, if they can be reused
+    /// rather than being fetched again, i.e. if they match `sha256` or, when no checksum is
+    /// pinned, if the cache is trusted.
+    fn cached(&self, url: &str, sha256: Option<&str>) -> Option<PathBuf> {
+        let path = self.path(url);
+        let bytes = fs::read(&path).ok()?;
+        match sha256 {
+            Some(expected) => (sha256_hex(&bytes) == expected.to_lowercase()).then_some(path),
+            None => self.trusted.then_some(path),
+        }
+    }
+
+    /// Checks the downloaded contents of `url` against [`REMOTE_SIZE_LIMIT`] and the `sha256`
+    /// checksum, if there is one, then caches them, returning the path of the cached file.
+    fn store(
+        &self,
+        url: &str,
+        bytes: &[u8],
+        sha256: Option<&str>,
+    ) -> std::result::Result<PathBuf, String> {
+        if bytes.len() as u64 > REMOTE_SIZE_LIMIT {
+            return Err(format!(
+                "Failed to download '{}': the file is larger than the limit of {} MiB.",
+                url,
+                REMOTE_SIZE_LIMIT / (1024 * 1024),
+            ));
+        }
+        if let Some(expected) = sha256 {
+            let found = sha256_hex(bytes);
+            if found != expected.to_lowercase() {
+                return Err(format!(
+                    "The contents of '{}' do not match the pinned `sha256` checksum (found {}).",
+                    url, found,
+                ));
+            }
+        }
+        let path = self.path(url);
+        fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&path, bytes))
+            .map_err(|err| format!("Failed to cache the contents of '{}': {}", url, err))?;
+        Ok(path)
+    }
+}
+
+/// Downloads the file at the URL specified by `source_path` into a [`RemoteCache`], returning
+/// the path of the cached file. If `sha256` is specified, the contents of the file must match
+/// this checksum. Previously downloaded files are reused where [`RemoteCache::cached`] allows.
+#[cfg(feature = "remote")]
+fn fetch_remote_source(source_path: &LitStr, sha256: Option<&str>) -> Result<PathBuf> {
+    use std::io::Read;
+    let url = source_path.value();
+    let cache = RemoteCache::new();
+    if let Some(path) = cache.cached(&url, sha256) {
+        return Ok(path);
+    }
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(REMOTE_CONNECT_TIMEOUT)
+        .timeout_read(REMOTE_READ_TIMEOUT)
+        .build();
+    let response = agent.get(&url).call().map_err(|err| {
+        Error::new(
+            source_path.span(),
+            format!("Failed to download '{}': {}", url, err),
+        )
+    })?;
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .take(REMOTE_SIZE_LIMIT + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|err| {
+            Error::new(
+                source_path.span(),
+                format!("Failed to download '{}': {}", url, err),
+            )
+        })?;
+    cache
+        .store(&url, &bytes, sha256)
+        .map_err(|message| Error::new(source_path.span(), message))
+}
+
+/// Returns `true` if the specified path contains any glob wildcards (`*`, `?`, or `[`).
+fn is_glob_pattern<S: AsRef<str>>(path: S) -> bool {
+    path.as_ref().contains(['*', '?', '['])
+}
+
+/// Resolves the `source_path` of a `docify::embed!(..)` call relative to `root`. If
+/// `source_path` is a glob pattern, all matching files are returned in sorted order, and a
+/// compile error is issued if there are none.
+fn resolve_source_paths(root: &Path, source_path: &LitStr) -> Result<Vec<PathBuf>> {
+    let value = source_path.value();
+    if !is_glob_pattern(&value) {
+        return Ok(vec![root.join(value)]);
+    }
+    let pattern = format!(
+        "{}/{}",
+        glob::Pattern::escape(&root.display().to_string()),
+        value
+    );
+    let paths = match glob::glob(&pattern) {
+        Ok(paths) => paths,
+        Err(err) => {
+            return Err(Error::new(
+                source_path.span(),
+                format!("Invalid glob pattern '{}': {}", value, err),
+            ))
+        }
+    };
+    let mut paths = paths
+        .filter_map(std::result::Result::ok)
+        .filter(|path| path.is_file())
+        .collect::<Vec<PathBuf>>();
+    if paths.is_empty() {
+        return Err(Error::new(
+            source_path.span(),
+            format!(
+                "No files matching the glob pattern '{}' could be found.",
+                value
+            ),
+        ));
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// A piece of source code excerpted from a file by `docify::embed!(..)`, prior to being
+/// converted into an example.
+struct Snippet {
+    /// The excerpted source code.
+    code: String,
+    /// The 1-based line number at which the excerpt starts in the original source file.
+    line: usize,
+    /// The 1-based line number at which the excerpt ends (inclusive) in the original source
+    /// file. This can't be derived from `code`, which may have had lines removed.
+    end_line: usize,
+    /// The description of the excerpted item, from its `#[docify::export(desc = "..")]`.
+    desc: Option<String>,
+}
+
+/// Embeds the part of the source file at `file_path` requested by `args`, returning the code
+/// of each resulting example, which has yet to be wrapped in a codeblock. `root` is the
+/// directory `file_path` was resolved against. See [`embed_codes`] for `allow_missing`.
+fn embed_file(
+    root: &Path,
+    file_path: &Path,
+    args: &EmbedArgs,
+    options: &EmbedOptions,
+    allow_missing: bool,
+) -> Result<Vec<String>> {
+    let cached = load_source_file(file_path).map_err(|err| {
+        let message = err.message(file_path, options.path_env.as_ref());
+        Error::new(args.file_path.span(), message)
+    })?;
+    embed_source(&cached, root, file_path, args, options, allow_missing)
+}
+
+/// Embeds the requested part of the specified (already loaded) source file, which was read
+/// from `file_path`, as in [`embed_file`].
+fn embed_source(
+    cached: &SourceFile,
+    root: &Path,
+    file_path: &Path,
+    args: &EmbedArgs,
+    options: &EmbedOptions,
+    allow_missing: bool,
+) -> Result<Vec<String>> {
+    let source_code = &cached.source;
+    let snippets = match &args.target {
+        Some(target) if options.marker.is_some() => {
+            return Err(Error::new_spanned(
+                target,
+                "The `marker` option cannot be combined with an `item_ident` or `line_range`.",
+            ));
+        }
+        Some(target) if options.category.is_some() => {
+            return Err(Error::new_spanned(
+                target,
+                "The `category` option cannot be combined with an `item_ident` or `line_range`.",
+            ));
+        }
+        Some(target) if options.regex.is_some() => {
+            return Err(Error::new_spanned(
+                target,
+                "The `regex` option cannot be combined with an `item_ident` or `line_range`.",
+            ));
+        }
+        Some(target) if options.region.is_some() => {
+            return Err(Error::new_spanned(
+                target,
+                "The `region` option cannot be combined with an `item_ident` or `line_range`.",
+            ));
+        }
+        None if options.region.is_some()
+            && (options.marker.is_some()
+                || options.from_marker.is_some()
+                || options.category.is_some()
+                || options.regex.is_some()) =>
+        {
+            return Err(Error::new(
+                args.file_path.span(),
+                "The `region` option cannot be combined with the `marker`, `from_marker`, \
+                `category`, or `regex` options.",
+            ));
+        }
+        None if options.region.is_some() => {
+            // regions are purely textual, so the file doesn't need to be valid rust
+            let region = options.region.as_deref().unwrap_or_default();
+            region_snippets(source_code, region).map_err(|message| {
+                Error::new(
+                    args.file_path.span(),
+                    format!("{} in '{}'.", message, file_path.display()),
+                )
+            })?
+        }
+        Some(target) if options.from_marker.is_some() => {
+            return Err(Error::new_spanned(
+                target,
+                "The `from_marker` option cannot be combined with an `item_ident` or \
+                `line_range`.",
+            ));
+        }
+        None if options.from_marker.is_some()
+            && (options.marker.is_some()
+                || options.category.is_some()
+                || options.regex.is_some()) =>
+        {
+            return Err(Error::new(
+                args.file_path.span(),
+                "The `from_marker` option cannot be combined with the `marker`, `category`, or \
+                `regex` options.",
+            ));
+        }
+        None if options.regex.is_some()
+            && (options.marker.is_some() || options.category.is_some()) =>
+        {
+            return Err(Error::new(
+                args.file_path.span(),
+                "The `regex` option cannot be combined with the `marker` or `category` options.",
+            ));
+        }
+        None if options.from_marker.is_some() => {
+            // like markers, this is purely textual, so the file doesn't need to be valid rust
+            let sentinel = options.from_marker.as_deref().unwrap_or_default();
+            let Some(index) = source_code.lines().position(|line| line.contains(sentinel)) else {
+                return Err(Error::new(
+                    args.file_path.span(),
+                    format!(
+                        "Could not find a line containing the `from_marker` sentinel `{}` in \
+                        '{}'.",
+                        sentinel,
+                        file_path.display()
+                    ),
+                ));
+            };
+            let code = source_code
+                .lines()
+                .skip(index + 1)
+                .collect::<Vec<&str>>()
+                .join("\n");
+            vec![Snippet {
+                code,
+                line: index + 2,
+                end_line: source_code.lines().count().max(index + 2),
+                desc: None,
+            }]
+        }
+        None if options.category.is_some() && options.marker.is_some() => {
+            return Err(Error::new(
+                args.file_path.span(),
+                "The `category` and `marker` options cannot be combined.",
+            ));
+        }
+        None if options.category.is_some() || options.regex.is_some() => {
+            let (search, described) = match (&options.category, &options.regex) {
+                (Some(category), _) => (
+                    ItemSearch::Category(category.clone()),
+                    format!("category '{}'", category),
+                ),
+                (None, regex) => {
+                    let regex = regex.clone().expect("either `category` or `regex` is set");
+                    let described = format!("a name matching regex '{}'", regex);
+                    (ItemSearch::Pattern(regex), described)
+                }
+            };
+            if let Some(lang) = &options.lang {
+                return Err(Error::new(
+                    args.file_path.span(),
+                    format!(
+                        "Cannot embed items with {} from a `lang = \"{}\"` file, only whole \
+                        files and line ranges can be embedded from non-rust files.",
+                        described, lang,
+                    ),
+                ));
+            }
+            let source_file = parse_source_file(source_code, file_path)?;
+            let mut visitor = ItemVisitor::new(search, None);
+            visitor.respect_cfg = options.respect_cfg;
+            visitor.visit_file(&source_file);
+            malformed_exports_error(&args.file_path, file_path, visitor.errors)?;
+            if visitor.results.is_empty() {
+                if allow_missing {
+                    return Ok(Vec::new());
+                }
+                return Err(Error::new(
+                    args.file_path.span(),
+                    format!(
+                        "Could not find any docify export items with {} in '{}'.",
+                        described,
+                        file_path.display(),
+                    ),
+                ));
+            }
+            item_snippets(
+                cached,
+                &source_file,
+                visitor.results,
+                None,
+                args,
+                file_path,
+                options,
+            )?
+        }
+        None if options.marker.is_some() => {
+            // markers are purely textual, so the file doesn't need to be valid rust
+            let marker = options.marker.as_deref().unwrap_or_default();
+            marker_snippets(source_code, marker).map_err(|message| {
+                Error::new(
+                    args.file_path.span(),
+                    format!("{} in '{}'.", message, file_path.display()),
+                )
+            })?
+        }
+        Some(target @ (EmbedTarget::Item(_) | EmbedTarget::Path(_))) => {
+            if let Some(lang) = &options.lang {
+                return Err(Error::new_spanned(
+                    target,
+                    format!(
+                        "Cannot embed item '{}' from a `lang = \"{}\"` file, only whole files \
+                        and line ranges can be embedded from non-rust files.",
+                        target.name(),
+                        lang,
+                    ),
+                ));
+            }
+            let source_file = parse_source_file(source_code, file_path)?;
+            let results = find_items(&source_file, target, options, file_path, allow_missing)?;
+            if results.is_empty() {
+                return Ok(Vec::new());
+            }
+            if options.unique {
+                ensure_unique(cached, &results, target, file_path)?;
+            }
+            item_snippets(
+                cached,
+                &source_file,
+                results,
+                Some(target),
+                args,
+                file_path,
+                options,
+            )?
+        }
+        Some(EmbedTarget::Lines(range)) => {
+            // line ranges are purely textual, so the file doesn't need to be valid rust
+            let code = range.excerpt(source_code, "file")?;
+            let line = range.start.base10_parse()?;
+            vec![Snippet {
+                end_line: line + code.lines().count().max(1) - 1,
+                code,
+                line,
+                desc: None,
+            }]
+        }
+        None => {
+            let mut code = source_code.clone();
+            if options.lang.is_none() {
+                // rather than wrapping arbitrary text in a rust codeblock, require non-rust
+                // files to specify their language
+                let source_file = parse_source_file(source_code, file_path).map_err(|err| {
+                    Error::new(
+                        args.file_path.span(),
+                        format!(
+                            "Cannot embed '{}' as a rust example because it does not parse as \
+                            valid rust: {}. To embed a non-rust file, specify its language, i.e. \
+                            `lang = \"text\"`.",
+                            file_path.strip_prefix(root).unwrap_or(file_path).display(),
+                            err
+                        ),
+                    )
+                })?;
+                ensure_flag_is_not_export(&source_file, args, file_path)?;
+                let mut visitor = HiddenItemVisitor::default();
+                visitor.visit_file(&source_file);
+                if !visitor.hidden.is_empty() {
+                    code = remove_hidden_items(cached, &visitor.hidden).map_err(|err| {
+                        Error::new(
+                            args.file_path.span(),
+                            format!(
+                                "Failed to remove hidden items from '{}': {}",
+                                file_path.display(),
+                                err
+                            ),
+                        )
+                    })?;
+                }
+            }
+            vec![Snippet {
+                code,
+                line: 1,
+                end_line: source_code.lines().count().max(1),
+                desc: None,
+            }]
+        }
+    };
+    let snippets = match (&options.lines, &args.target) {
+        (None, _) => snippets,
+        (Some(range), Some(target @ (EmbedTarget::Item(_) | EmbedTarget::Path(_)))) => {
+            let what = format!("item '{}'", target.name());
+            let start = range.start.base10_parse::<usize>()?;
+            snippets
+                .into_iter()
+                .map(|snippet| {
+                    let code = fix_leading_indentation(range.excerpt(&snippet.code, &what)?);
+                    let line = snippet.line + start - 1;
+                    Ok(Snippet {
+                        end_line: line + code.lines().count().max(1) - 1,
+                        code,
+                        line,
+                        desc: snippet.desc,
+                    })
+                })
+                .collect::<Result<Vec<Snippet>>>()?
+        }
+        (Some(range), _) => {
+            return Err(Error::new_spanned(
+                range,
+                "The `lines` option can only be combined with an `item_ident`, use a \
+                `line_range` in its place to embed lines of the whole file.",
+            ));
+        }
+    };
+    let display_path = match is_remote_url(args.file_path.value()) {
+        true => args.file_path.value(),
+        false => file_path
+            .strip_prefix(root)
+            .unwrap_or(file_path)
+            .display()
+            .to_string(),
+    };
+    let mut codes: Vec<String> = Vec::new();
+    for snippet in snippets {
+        // the lines of the original file the snippet was excerpted from, before anything is
+        // elided from it
+        let metadata = options.emit_metadata.then(|| {
+            let item = match &args.target {
+                Some(target @ (EmbedTarget::Item(_) | EmbedTarget::Path(_))) => {
+                    json_string(&target.name())
+                }
+                _ => String::from("null"),
+            };
+            format!(
+                "{}{{\"path\":{},\"item\":{},\"lines\":[{},{}]}} -->",
+                METADATA_PREFIX,
+                json_string(&display_path),
+                item,
+                snippet.line,
+                snippet.end_line
+            )
+        });
+        // redact first, so a redacted region inside a hidden region can't be left half-open
+        let mut code = REDACTED_REGION
+            .replace(&snippet.code)
+            .and_then(|code| HIDDEN_REGION.replace_with(&code, options.placeholder()))
+            .map_err(|message| {
+                Error::new(
+                    args.file_path.span(),
+                    format!("{} in '{}'.", message, file_path.display()),
+                )
+            })?;
+        if options.strip_docs {
+            code = strip_doc_comments(&code);
+        }
+        if !options.strip_attrs.is_empty() {
+            code = strip_attributes(&code, &options.strip_attrs);
+        }
+        if options.no_attrs {
+            code = strip_attributes_where(&code, |path, _| path != "doc");
+        }
+        if options.strip_test {
+            code = strip_attributes_where(&code, |path, _| {
+                ["test", "should_panic", "ignore"].contains(&path) || path.ends_with("::test")
+            });
+        }
+        let item_embed = matches!(
+            args.target,
+            Some(EmbedTarget::Item(_) | EmbedTarget::Path(_))
+        ) || options.category.is_some()
+            || options.regex.is_some();
+        if item_embed && !options.keep_doc_hidden {
+            code = strip_doc_hidden(&code);
+        }
+        if options.dedent {
+            code = fix_indentation(code);
+        }
+        if let Some(max_width) = options.max_width {
+            code = wrap_comments(&code, max_width);
+        }
+        // examples embedded in a single codeblock are wrapped as a whole by `embed_with`
+        if options.wrap_main && !options.single_fence && syn::parse_str::<File>(&code).is_err() {
+            code = wrap_in_main(&code);
+        }
+        // line ranges and markers are purely textual, so they are often fragments
+        let textual = matches!(args.target, Some(EmbedTarget::Lines(_)))
+            || options.marker.is_some()
+            || options.from_marker.is_some()
+            || options.region.is_some();
+        if options.check_syntax && !textual {
+            check_example_syntax(&code).map_err(|err| {
+                Error::new(
+                    args.file_path.span(),
+                    format!(
+                        "The example embedded from {}:{} would fail as a doc test because it \
+                        does not parse as valid rust: {}. Specify the `no_check` option if this \
+                        is intentional.",
+                        display_path, snippet.line, err
+                    ),
+                )
+            })?;
+        }
+        if options.line_numbers {
+            code = add_line_numbers(&code);
+        }
+        if options.show_source {
+            if !code.ends_with('\n') {
+                code.push('\n');
+            }
+            code.push_str(&format!("// from {}:{}", display_path, snippet.line));
+        }
+        // header lines, which are moved in front of the codeblock by `embed_with`
+        if let Some(metadata) = metadata {
+            code = format!("{}\n{}", metadata, code);
+        }
+        if options.show_desc {
+            let desc = snippet.desc.as_deref().map(escape_markdown);
+            code = format!("{}\n{}", desc.unwrap_or_default(), code);
+        }
+        codes.push(code);
+    }
+    Ok(codes)
+}
+
+/// Returns the names among `candidates` that are closest to `search` by edit distance, as long
+/// as they are close enough to plausibly be a typo of it, in sorted order.
+fn closest_candidates(search: &Ident, candidates: &[Ident]) -> Vec<String> {
+    let search = search.to_string();
+    let max_distance = (search.chars().count() / 3).max(1);
+    let mut names = candidates
+        .iter()
+        .map(|candidate| candidate.to_string())
+        .filter(|name| *name != search)
+        .collect::<Vec<String>>();
+    names.sort();
+    names.dedup();
+    let distances = names
+        .iter()
+        .map(|name| edit_distance(&search, name))
+        .collect::<Vec<usize>>();
+    let Some(&closest) = distances.iter().min() else {
+        return Vec::new();
+    };
+    if closest > max_distance {
+        return Vec::new();
+    }
+    names
+        .into_iter()
+        .zip(distances)
+        .filter(|&(_, distance)| distance == closest)
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Returns the Levenshtein distance between the specified strings, i.e. the number of single
+/// character insertions, deletions, or substitutions needed to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut prev = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+/// Issues a single compile error, at the span of `target`, listing the malformed export
+/// attributes found by an [`ItemVisitor`] in the file at `file_path`, if there are any.
+fn malformed_exports_error<T: ToTokens>(
+    target: &T,
+    file_path: &Path,
+    errors: Vec<Error>,
+) -> Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let messages = errors
+        .iter()
+        .map(|err| err.to_string())
+        .collect::<Vec<String>>();
+    Err(Error::new_spanned(
+        target,
+        format!(
+            "Found {} malformed docify export attribute(s) in '{}': {}",
+            errors.len(),
+            file_path.display(),
+            messages.join(" "),
+        ),
+    ))
+}
+
+/// Wraps each line of the specified code consisting only of a `//`-style comment (including doc
+/// comments) that is longer than `max_width` characters at word boundaries, continuing the
+/// comment on the following lines with the same indentation and comment prefix. Words longer
+/// than the available width are left intact.
+fn wrap_comments(code: &str, max_width: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for line in code.lines() {
+        let trimmed = line.trim_start();
+        if line.chars().count() <= max_width || !trimmed.starts_with("//") {
+            lines.push(line.to_string());
+            continue;
+        }
+        let indent = &line[..line.len() - trimmed.len()];
+        let marker_len = trimmed
+            .find(|c: char| c != '/' && c != '!')
+            .unwrap_or(trimmed.len());
+        let prefix = format!("{}{} ", indent, &trimmed[..marker_len]);
+        let mut current = prefix.clone();
+        for word in trimmed[marker_len..].split_whitespace() {
+            let fits = current.chars().count() + word.chars().count() <= max_width;
+            if !fits && current.len() > prefix.len() {
+                lines.push(current.trim_end().to_string());
+                current = prefix.clone();
+            }
+            current.push_str(word);
+            current.push(' ');
+        }
+        lines.push(current.trim_end().to_string());
+    }
+    lines.join("\n")
+}
+
+/// Wraps the specified code, such as a sequence of statements, in a `fn main() { .. }`, indenting
+/// it by one level.
+fn wrap_in_main(code: &str) -> String {
+    let body = code
+        .trim_matches('\n')
+        .lines()
+        .map(|line| match line.trim().is_empty() {
+            true => String::new(),
+            false => format!("    {}", line),
+        })
+        .collect::<Vec<String>>();
+    format!("fn main() {{\n{}\n}}", body.join("\n"))
+}
+
+/// Checks that the specified code parses either as a rust source [`File`] or, since rustdoc
+/// wraps doc tests in a `main` function when needed, as a sequence of statements.
+fn check_example_syntax(code: &str) -> Result<()> {
+    // syn accepts some invalid items, such as functions without a body, as verbatim tokens
+    let reject_verbatim = |items: Vec<&Item>| match items
+        .into_iter()
+        .find(|item| matches!(item, Item::Verbatim(_)))
+    {
+        Some(item) => Err(Error::new_spanned(item, "unsupported item")),
+        None => Ok(()),
+    };
+    let err = match syn::parse_str::<File>(code) {
+        Ok(file) => match reject_verbatim(file.items.iter().collect()) {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        },
+        Err(err) => err,
+    };
+    match syn::parse_str::<syn::Block>(&format!("{{\n{}\n}}", code)) {
+        Ok(block) => reject_verbatim(
+            block
+                .stmts
+                .iter()
+                .filter_map(|stmt| match stmt {
+                    Stmt::Item(item) => Some(item),
+                    _ => None,
+                })
+                .collect(),
+        )
+        .map_err(|_| err),
+        Err(_) => Err(err),
+    }
+}
+
+/// Finds the items in `source_file` matching the specified (item or path) `target`, along with
+/// their [`ResultStyle`] and the path of the module they were found in. A compile error is
+/// issued if there are none, unless `allow_missing` is `true`.
+fn find_items(
+    source_file: &File,
+    target: &EmbedTarget,
+    options: &EmbedOptions,
+    file_path: &Path,
+    allow_missing: bool,
+) -> Result<Vec<FoundItem>> {
+    let (results, candidates, found_exports) = match target {
+        EmbedTarget::Path(path) => {
+            // a path may refer to an exported item inside a module, an impl member, or
+            // an enum variant
+            let mut segments: Vec<Ident> =
+                path.segments.iter().map(|seg| seg.ident.clone()).collect();
+            let search = segments.pop().expect("paths have at least one segment");
+            let mut visitor = ItemVisitor::new(ItemSearch::Name(search), Some(segments));
+            visitor.respect_cfg = options.respect_cfg;
+            visitor.visit_file(source_file);
+            malformed_exports_error(target, file_path, visitor.errors)?;
+            let candidates = visitor.candidates;
+            let found_exports = visitor.found_exports;
+            let mut results = visitor.results;
+            if let Some(mut visitor) = MemberVisitor::new(path) {
+                visitor.respect_cfg = options.respect_cfg;
+                visitor.visit_file(source_file);
+                results.extend(visitor.results);
+            }
+            (results, candidates, found_exports)
+        }
+        EmbedTarget::Item(ident) => {
+            let mut visitor = ItemVisitor::new(ItemSearch::Name(ident.clone()), None);
+            visitor.respect_cfg = options.respect_cfg;
+            visitor.visit_file(source_file);
+            malformed_exports_error(target, file_path, visitor.errors)?;
+            (visitor.results, visitor.candidates, visitor.found_exports)
+        }
+        EmbedTarget::Lines(_) => unreachable!(),
+    };
+    if results.is_empty() && allow_missing {
+        return Ok(results);
+    }
+    if results.is_empty() {
+        let mut message = match target {
+            EmbedTarget::Path(_) => format!(
+                "Could not find docify export item or type member '{}' in '{}'.",
+                target.name(),
+                file_path.display(),
+            ),
+            _ => format!(
+                "Could not find docify export item '{}' in '{}'.",
+                target.name(),
+                file_path.display(),
+            ),
+        };
+        let search = match target {
+            EmbedTarget::Path(path) => path.segments.last().map(|seg| &seg.ident),
+            EmbedTarget::Item(ident) => Some(ident),
+            EmbedTarget::Lines(_) => None,
+        };
+        if let (false, Some(search)) = (found_exports, search) {
+            // the most likely mistake is forgetting to annotate the item in the first place
+            message.push_str(&format!(
+                " The file '{}' contains no #[docify::export] items at all, so make sure the \
+                item is annotated with #[docify::export] (or #[docify::export({})]).",
+                file_path.display(),
+                search,
+            ));
+            return Err(Error::new_spanned(target, message));
+        }
+        if let Some(search) = search {
+            let suggestions = closest_candidates(search, &candidates);
+            if !suggestions.is_empty() {
+                let suggestions = suggestions
+                    .iter()
+                    .map(|name| format!("'{}'", name))
+                    .collect::<Vec<String>>();
+                message.push_str(&format!(" Did you mean {}?", suggestions.join(" or ")));
+            }
+        }
+        return Err(Error::new_spanned(target, message));
+    }
+    Ok(results)
+}
+
+/// Returns the inherent name of the specified item, member, variant, or statement, if it has
+/// one, for the `sort` option.
+fn result_name(item: &TokenStream2) -> Option<String> {
+    let name = if let Some(foreign_item) = parse_foreign_item(item) {
+        foreign_item.name_ident()
+    } else if let Ok(item) = parse2::<Item>(item.clone()) {
+        item.name_ident()
+    } else if let Ok(impl_item) = parse2::<ImplItem>(item.clone()) {
+        impl_item.name_ident()
+    } else if let Ok(trait_item) = parse2::<TraitItem>(item.clone()) {
+        trait_item.name_ident()
+    } else if let Ok(variant) = parse2::<syn::Variant>(item.clone()) {
+        Some(variant.ident)
+    } else {
+        Parser::parse2(Block::parse_within, item.clone())
+            .ok()
+            .and_then(|stmts| stmts.first().and_then(|stmt| stmt.name_ident()))
+    };
+    name.map(|name| name.unraw().to_string())
+}
+
+/// Excerpts each of the `results` found in the specified source file for `target` (or for the
+/// `category` or `regex` option, if `None`), applying the options that only make sense for
+/// items.
+fn item_snippets(
+    cached: &SourceFile,
+    source_file: &File,
+    results: Vec<FoundItem>,
+    target: Option<&EmbedTarget>,
+    args: &EmbedArgs,
+    file_path: &Path,
+    options: &EmbedOptions,
+) -> Result<Vec<Snippet>> {
+    let source_code = &cached.source;
+    let mut results = results;
+    match options.sort {
+        SortOrder::Source => (),
+        SortOrder::Reverse => results.reverse(),
+        // the sort is stable, so items with the same name stay in source order
+        SortOrder::Name => results.sort_by_cached_key(|(item, ..)| result_name(item)),
+    }
+    let mut snippets: Vec<Snippet> = Vec::new();
+    for (item, style, modules, desc) in results {
+        let excerpt_error = |err: Error| {
+            Error::new(
+                args.file_path.span(),
+                format!(
+                    "Failed to excerpt embedded source from '{}': {}",
+                    file_path.display(),
+                    err
+                ),
+            )
+        };
+        let style = match (options.contents_only, style) {
+            (true, ResultStyle::Export) => {
+                let has_content = matches!(
+                    parse2::<Item>(item.clone()),
+                    Ok(Item::Mod(ItemMod {
+                        content: Some(_),
+                        ..
+                    }) | Item::Fn(_)
+                        | Item::Impl(_)
+                        | Item::Trait(_)
+                        | Item::ForeignMod(_))
+                );
+                if !has_content {
+                    return Err(Error::new(
+                        args.file_path.span(),
+                        format!(
+                            "The `contents_only` option can only be used with modules, \
+                            functions, `impl` blocks, and traits, but an item matching the \
+                            request in '{}' is something else.",
+                            file_path.display()
+                        ),
+                    ));
+                }
+                ResultStyle::ExportContent
+            }
+            _ => style,
+        };
+        let range = source_excerpt_range(cached, &item, style).map_err(excerpt_error)?;
+        let excerpt = match (options.signature_only, options.signatures_only) {
+            _ if options.value_only => value_excerpt(cached, &item),
+            _ if options.pub_only => public_excerpt(cached, &item, style),
+            (true, _) => signature_excerpt(cached, &item),
+            (false, true) => trait_signatures_excerpt(cached, &item),
+            (false, false) => source_excerpt(cached, &item, style),
+        }
+        .map_err(excerpt_error)?;
+        let excerpt = match options.eval_const {
+            true => annotate_const_value(&item, excerpt).map_err(excerpt_error)?,
+            false => excerpt,
+        };
+        let excerpt = match options.with_preamble {
+            true => {
+                let preamble = preamble_excerpt(source_code, range.start);
+                match preamble.is_empty() {
+                    true => excerpt,
+                    false => format!("{}\n{}", preamble, excerpt),
+                }
+            }
+            false => excerpt,
+        };
+        let formatted = fix_indentation(excerpt);
+        let formatted = match (style, target) {
+            (ResultStyle::Variant, Some(EmbedTarget::Path(path))) => {
+                let enum_ident = &path.segments[path.segments.len() - 2].ident;
+                into_variant_example(enum_ident, &formatted, options.placeholder())
+            }
+            _ => formatted,
+        };
+        let formatted = match options.with_module_path {
+            true => into_module_example(&modules, &formatted),
+            false => formatted,
+        };
+        let formatted = match options.with_imports {
+            true => {
+                let imports = referenced_imports(source_file, &item)
+                    .iter()
+                    .map(|item_use| source_excerpt(cached, item_use, ResultStyle::Export))
+                    .collect::<Result<Vec<String>>>()
+                    .map_err(excerpt_error)?;
+                match imports.is_empty() {
+                    true => formatted,
+                    false => format!("{}\n\n{}", imports.join("\n"), formatted),
+                }
+            }
+            false => formatted,
+        };
+        snippets.push(Snippet {
+            code: formatted,
+            line: line_number(source_code, range.start),
+            end_line: line_number(source_code, range.end),
+            desc,
+        });
+    }
+    Ok(snippets)
+}
+
+/// Issues a compile error listing the line of each result if more than one item matched the
+/// specified target, for use with the `unique` option.
+fn ensure_unique(
+    file: &SourceFile,
+    results: &[FoundItem],
+    target: &EmbedTarget,
+    file_path: &Path,
+) -> Result<()> {
+    if results.len() > 1 {
+        let lines = results
+            .iter()
+            .map(|(item, style, ..)| {
+                let range = source_excerpt_range(file, item, *style)?;
+                Ok(line_number(&file.source, range.start).to_string())
+            })
+            .collect::<Result<Vec<String>>>()?;
+        return Err(Error::new_spanned(
+            target,
+            format!(
+                "Found {} docify export items matching '{}' in '{}' (on lines {}), \
+                but `unique` was specified.",
+                results.len(),
+                target.name(),
+                file_path.display(),
+                lines.join(", "),
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Prefixes each line of the specified code with a right-aligned `N | ` gutter, where `N` is
+/// the 1-based position of the line within the code.
+fn add_line_numbers(code: &str) -> String {
+    let width = code.lines().count().to_string().len();
+    code.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            format!("{:>width$} | {}", i + 1, line)
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Returns `true` if `line` contains the specified marker, i.e. `docify:begin-foo`, and the
+/// marker isn't merely the start of a longer one, i.e. `docify:begin-foobar`.
+fn contains_marker(line: &str, marker: &str) -> bool {
+    line.match_indices(marker).any(|(i, _)| {
+        !line[i + marker.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '-')
+    })
+}
+
+/// Excerpts every region of `source` between a line containing `docify:begin-{name}` and the
+/// next line containing `docify:end-{name}`, excluding the marker lines themselves.
+fn marker_snippets(source: &str, name: &str) -> std::result::Result<Vec<Snippet>, String> {
+    let begin = format!("docify:begin-{}", name);
+    let end = format!("docify:end-{}", name);
+    let mut snippets: Vec<Snippet> = Vec::new();
+    let mut current: Option<(usize, Vec<&str>)> = None;
+    for (i, line) in source.lines().enumerate() {
+        match &mut current {
+            None if contains_marker(line, &begin) => current = Some((i + 2, Vec::new())),
+            None if contains_marker(line, &end) => {
+                return Err(format!(
+                    "Found `{}` without a preceding `{}` on line {}",
+                    end,
+                    begin,
+                    i + 1
+                ))
+            }
+            None => continue,
+            Some((line_number, lines)) if contains_marker(line, &end) => {
+                snippets.push(Snippet {
+                    code: lines.join("\n"),
+                    line: *line_number,
+                    end_line: *line_number + lines.len().max(1) - 1,
+                    desc: None,
+                });
+                current = None;
+            }
+            Some((_, lines)) => lines.push(line),
+        }
+    }
+    if let Some((line_number, _)) = current {
+        return Err(format!(
+            "Found `{}` without a matching `{}` on line {}",
+            begin,
+            end,
+            line_number - 1
+        ));
+    }
+    if snippets.is_empty() {
+        return Err(format!("Could not find the marker `{}`", begin));
+    }
+    Ok(snippets)
+}
+
+/// Excerpts every region of `source` between a line containing `docify:region-start {name}` and
+/// the line containing the `docify:region-end` that closes it, excluding the marker lines of the
+/// region and of any regions nested inside it, with their shared leading indentation removed.
+fn region_snippets(source: &str, name: &str) -> std::result::Result<Vec<Snippet>, String> {
+    const START: &str = "docify:region-start";
+    const END: &str = "docify:region-end";
+    let mut snippets: Vec<Snippet> = Vec::new();
+    // the name and (1-based) line number of each open region, innermost last
+    let mut open: Vec<(&str, usize)> = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        if let Some(index) = line.find(START) {
+            let region = line[index + START.len()..]
+                .split_whitespace()
+                .next()
+                .unwrap_or_default();
+            if region.is_empty() {
+                return Err(format!(
+                    "Found `{}` without a region name on line {}",
+                    START,
+                    i + 1
+                ));
+            }
+            if let Some((_, line_number)) = open.iter().find(|(open, _)| *open == region) {
+                return Err(format!(
+                    "Found `{} {}` on line {} inside the region of the same name starting on \
+                    line {}, regions with the same name cannot overlap",
+                    START,
+                    region,
+                    i + 1,
+                    line_number
+                ));
+            }
+            open.push((region, i + 1));
+            continue;
+        }
+        if contains_marker(line, END) {
+            let Some((region, line_number)) = open.pop() else {
+                return Err(format!(
+                    "Found `{}` without a preceding `{}` on line {}",
+                    END,
+                    START,
+                    i + 1
+                ));
+            };
+            if region == name {
+                let code = fix_indentation(lines.join("\n"));
+                snippets.push(Snippet {
+                    end_line: line_number + code.lines().count().max(1),
+                    code,
+                    line: line_number + 1,
+                    desc: None,
+                });
+            }
+            if !open.iter().any(|(open, _)| *open == name) {
+                lines.clear();
+            }
+            continue;
+        }
+        if open.iter().any(|(open, _)| *open == name) {
+            lines.push(line);
+        }
+    }
+    if let Some((region, line_number)) = open.pop() {
+        return Err(format!(
+            "Found `{} {}` without a matching `{}` on line {}",
+            START, region, END, line_number
+        ));
+    }
+    if snippets.is_empty() {
+        return Err(format!("Could not find the region `{} {}`", START, name));
+    }
+    Ok(snippets)
+}
+
+/// Removes all lines consisting only of a doc comment (`///` or `//!`) or a `#[doc = ".."]`
+/// attribute from the specified code. Regular comments, including `////` comments, are kept.
+fn strip_doc_comments(code: &str) -> String {
+    code.lines()
+        .filter(|line| {
+            let line = line.trim();
+            let doc_comment =
+                (line.starts_with("///") && !line.starts_with("////")) || line.starts_with("//!");
+            // inner `#![doc = ".."]` attributes are matched as if they were outer ones
+            let attr = match line.strip_prefix("#!") {
+                Some(rest) => format!("#{}", rest),
+                None => line.to_string(),
+            };
+            let doc_attr = DOC_COMMENT_ATTR
+                .find(&attr)
+                .is_some_and(|m| m.range() == (0..attr.len()));
+            !doc_comment && !doc_attr
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+/// Removes the outer attributes whose path is one of `paths`, or is nested inside one of them,
+/// from the specified code, along with the lines they occupied if nothing else is left on them.
+fn strip_attributes(code: &str, paths: &[String]) -> String {
+    strip_attributes_where(code, |path, _| {
+        paths
+            .iter()
+            .any(|strip| path == strip || path.starts_with(&format!("{}::", strip)))
+    })
+}
+
+/// Removes the `#[doc(hidden)]` attributes from the specified code, for embeds of items that
+/// are hidden from the docs of their own crate. See [`strip_attributes`].
+fn strip_doc_hidden(code: &str) -> String {
+    strip_attributes_where(code, |path, attr| {
+        path == "doc"
+            && attr
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .eq("#[doc(hidden)]".chars())
+    })
+}
+
+/// Removes the outer attributes for which `should_strip` returns `true` when passed their path
+/// (without whitespace) and their full text from the specified code, as in
+/// [`strip_attributes`].
+fn strip_attributes_where(code: &str, should_strip: impl Fn(&str, &str) -> bool) -> String {
+    let literals = literal_positions(code);
+    let bytes = code.as_bytes();
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for m in find_outside_literals(&ATTRIBUTE_START, code, &literals) {
+        let line_start = line_start_position(code, m.start());
+        if code[line_start..m.start()].contains("//") {
+            continue;
+        }
+        let Some(captures) = ATTRIBUTE_START.captures_at(code, m.start()) else {
+            continue;
+        };
+        let path: String = captures[1].chars().filter(|c| !c.is_whitespace()).collect();
+        // find the `]` closing the attribute, skipping over any nested brackets and literals
+        let mut depth = 0;
+        let mut end = None;
+        for (i, byte) in bytes.iter().enumerate().skip(m.start()) {
+            if literals[i] {
+                continue;
+            }
+            match byte {
+                b'[' | b'(' | b'{' => depth += 1,
+                b']' | b')' | b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(i + 1);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let Some(mut end) = end else {
+            continue;
+        };
+        if !should_strip(&path, &code[m.start()..end]) {
+            continue;
+        }
+        let mut start = m.start();
+        let rest = &code[end..];
+        let line_rest = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        if code[line_start..start].trim().is_empty() && line_rest.trim().is_empty() {
+            // nothing else is on the attribute's line(s), so remove them entirely
+            start = line_start;
+            end = min(end + line_rest.len() + 1, code.len());
+        } else {
+            // also remove whitespace separating the attribute from whatever follows it
+            end += rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        }
+        ranges.push(start..end);
+    }
+    let mut stripped = String::new();
+    let mut cursor = 0;
+    for range in ranges {
+        if range.start < cursor {
+            continue;
+        }
+        stripped.push_str(&code[cursor..range.start]);
+        cursor = range.end;
+    }
+    stripped.push_str(&code[cursor..]);
+    stripped
+}
+
+/// Wraps the source code of an enum variant in an elided copy of the enum it belongs to, so it
+/// reads as valid rust, with the other variants replaced by `placeholder`.
+fn into_variant_example(enum_ident: &Ident, variant: &str, placeholder: &str) -> String {
+    let mut lines = vec![
+        format!("enum {} {{", enum_ident),
+        format!("    {}", placeholder),
+    ];
+    for line in variant.lines() {
+        lines.push(match line.is_empty() {
+            true => String::new(),
+            false => format!("    {}", line),
+        });
+    }
+    lines.last_mut().unwrap().push(',');
+    lines.push(format!("    {}", placeholder));
+    lines.push(String::from("}"));
+    lines.join("\n")
+}
+
+/// Returns the top-level `use` items of `file` that import an ident appearing anywhere in
+/// `item`, along with any glob imports, since what those import can't be known.
+fn referenced_imports(file: &File, item: &TokenStream2) -> Vec<ItemUse> {
+    let mut referenced: HashSet<String> = HashSet::new();
+    collect_idents(item.clone(), &mut referenced);
+    file.items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Use(item_use) => Some(item_use),
+            _ => None,
+        })
+        .filter(|item_use| {
+            let mut imported: Vec<Option<String>> = Vec::new();
+            collect_use_idents(&item_use.tree, &mut imported);
+            imported.iter().any(|ident| match ident {
+                Some(ident) => referenced.contains(ident),
+                None => true,
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Adds the name of every ident in `tokens`, including those inside groups, to `idents`.
+fn collect_idents(tokens: TokenStream2, idents: &mut HashSet<String>) {
+    for tt in tokens {
+        match tt {
+            proc_macro2::TokenTree::Ident(ident) => {
+                idents.insert(ident.to_string());
+            }
+            proc_macro2::TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            _ => {}
+        }
+    }
+}
+
+/// Adds the name each leaf of `tree` is imported as to `idents`, or `None` for glob imports.
+fn collect_use_idents(tree: &UseTree, idents: &mut Vec<Option<String>>) {
+    match tree {
+        UseTree::Path(use_path) => collect_use_idents(&use_path.tree, idents),
+        UseTree::Name(use_name) if use_name.ident == "self" => idents.push(None),
+        UseTree::Name(use_name) => idents.push(Some(use_name.ident.to_string())),
+        UseTree::Rename(use_rename) => idents.push(Some(use_rename.rename.to_string())),
+        UseTree::Glob(_) => idents.push(None),
+        UseTree::Group(use_group) => use_group
+            .items
+            .iter()
+            .for_each(|tree| collect_use_idents(tree, idents)),
+    }
+}
+
+/// Wraps the source code of an item in the (otherwise empty) modules it is nested in, outermost
+/// first, so readers can see where it lives.
+fn into_module_example(modules: &[Ident], code: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for (depth, module) in modules.iter().enumerate() {
+        lines.push(format!("{}mod {} {{", "    ".repeat(depth), module));
+    }
+    let indent = "    ".repeat(modules.len());
+    for line in code.lines() {
+        lines.push(match line.is_empty() {
+            true => String::new(),
+            false => format!("{}{}", indent, line),
+        });
+    }
+    for depth in (0..modules.len()).rev() {
+        lines.push(format!("{}}}", "    ".repeat(depth)));
+    }
+    lines.join("\n")
+}
+
+/// A region of lines in embedded code delimited by a pair of marker comments, each on its own
+/// line, which is replaced by a placeholder comment when embedding.
+struct MarkedRegion {
+    start: &'static str,
+    end: &'static str,
+    placeholder: &'static str,
+}
+
+/// Lines that are omitted from embedded examples because they aren't relevant to readers.
+const HIDDEN_REGION: MarkedRegion = MarkedRegion {
+    start: "// docify:hide-start",
+    end: "// docify:hide-end",
+    placeholder: "// ...",
+};
+
+/// Lines that must not appear in published docs, such as placeholder secrets.
+const REDACTED_REGION: MarkedRegion = MarkedRegion {
+    start: "// docify:redact-start",
+    end: "// docify:redact-end",
+    placeholder: "/* redacted */",
+};
+
+impl MarkedRegion {
+    /// Replaces each of these regions in `code`, from the start marker to the end marker
+    /// (inclusive), with a single placeholder line indented like the start marker.
+    fn replace(&self, code: &str) -> std::result::Result<String, String> {
+        self.replace_with(code, self.placeholder)
+    }
+
+    /// Like [`MarkedRegion::replace`], but with the specified placeholder rather than the
+    /// default one.
+    fn replace_with(&self, code: &str, placeholder: &str) -> std::result::Result<String, String> {
+        let mut lines: Vec<String> = Vec::new();
+        let mut region_start: Option<usize> = None;
+        for (i, line) in code.lines().enumerate() {
+            let marker = line.trim();
+            match region_start {
+                None if marker == self.start => {
+                    let indent = &line[..line.len() - line.trim_start().len()];
+                    lines.push(format!("{}{}", indent, placeholder));
+                    region_start = Some(i + 1);
+                }
+                None if marker == self.end => {
+                    return Err(format!(
+                        "Found `{}` without a preceding `{}` on line {} of the embedded code",
+                        self.end,
+                        self.start,
+                        i + 1
+                    ))
+                }
+                None => lines.push(line.to_string()),
+                Some(_) if marker == self.end => region_start = None,
+                Some(_) => continue,
+            }
+        }
+        if let Some(line) = region_start {
+            return Err(format!(
+                "Found `{}` without a matching `{}` on line {} of the embedded code",
+                self.start, self.end, line
+            ));
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Inner version of [`embed_diff_internal`] that just returns the result as a [`String`].
+fn embed_diff_internal_str(tokens: impl Into<TokenStream2>) -> Result<String> {
+    let args = parse2::<EmbedDiffArgs>(tokens.into())?;
+    let new_args = args.new_args;
+    let old_args = EmbedArgs {
+        file_path: args.old_file_path,
+        file_paths: Vec::new(),
+        ..new_args.clone()
+    };
+    let options = EmbedOptions::from_options(&new_args.options)?;
+    if let Some(option) = new_args
+        .options
+        .iter()
+        .find(|option| option.name == "emit_metadata")
+    {
+        return Err(Error::new(
+            option.name.span(),
+            "The `emit_metadata` option is not supported by `docify::embed_diff!(..)`.",
+        ));
+    }
+    let old = embed_codes(&old_args, &options, true)?;
+    let new = embed_codes(&new_args, &options, true)?;
+    if old.is_empty() && new.is_empty() {
+        let message = match &new_args.target {
+            Some(target) => format!(
+                "Could not find docify export item '{}' in either '{}' or '{}'.",
+                target.name(),
+                old_args.file_path.value(),
+                new_args.file_path.value(),
+            ),
+            None => String::from("Both files are empty."),
+        };
+        return Err(Error::new(new_args.file_path.span(), message));
+    }
+    let old = old.join("\n\n");
+    let new = new.join("\n\n");
+    let diff = similar::TextDiff::from_lines(old.as_str(), new.as_str());
+    let mut lines: Vec<String> = Vec::new();
+    for change in diff.iter_all_changes() {
+        let sign = match change.tag() {
+            similar::ChangeTag::Delete => '-',
+            similar::ChangeTag::Insert => '+',
+            similar::ChangeTag::Equal => ' ',
+        };
+        lines.push(
+            format!("{}{}", sign, change.value().trim_end_matches('\n'))
+                .trim_end()
+                .to_string(),
+        );
+    }
+    let info = options.fence.as_deref().unwrap_or("diff");
+    Ok(into_titled_example(
+        &lines.join("\n"),
+        info,
+        options.title.as_deref(),
+        options.title_style,
+    ))
+}
+
+/// Internal implementation behind [`macro@embed_diff`].
+fn embed_diff_internal(tokens: impl Into<TokenStream2>) -> Result<TokenStream2> {
+    let output = embed_diff_internal_str(tokens)?;
+    Ok(quote!(#output))
+}
+
+/// Inner version of [`embed_docs_internal`] that just returns the result as a [`String`].
+fn embed_docs_internal_str(tokens: impl Into<TokenStream2>) -> Result<String> {
+    let args = parse2::<EmbedArgs>(tokens.into())?;
+    let options = EmbedOptions::from_options(&args.options)?;
+    let target = match &args.target {
+        Some(target @ (EmbedTarget::Item(_) | EmbedTarget::Path(_))) => target,
+        Some(target @ EmbedTarget::Lines(_)) => {
+            return Err(Error::new_spanned(
+                target,
+                "`embed_docs!` requires an `item_ident`, not a `line_range`.",
+            ));
+        }
+        None => {
+            return Err(Error::new(
+                args.file_path.span(),
+                "`embed_docs!` requires an `item_ident` to take the doc comments of.",
+            ));
+        }
+    };
+    let Some((_, file_paths)) = source_files(&args, &options)? else {
+        return Ok(String::new());
+    };
+    let mut docs: Vec<String> = Vec::new();
+    for file_path in file_paths {
+        let cached = load_source_file(&file_path).map_err(|e| {
+            Error::new(
+                args.file_path.span(),
+                e.message(&file_path, options.path_env.as_ref()),
+            )
+        })?;
+        let source_file = parse_source_file(&cached.source, &file_path)?;
+        let results = find_items(&source_file, target, &options, &file_path, false)?;
+        if options.unique {
+            ensure_unique(&cached, &results, target, &file_path)?;
+        }
+        for (item, ..) in results {
+            let text = doc_comment_text(item)?;
+            if text.is_empty() {
+                return Err(Error::new_spanned(
+                    target,
+                    format!(
+                        "Docify export item '{}' in '{}' has no doc comments.",
+                        target.name(),
+                        file_path.display(),
+                    ),
+                ));
+            }
+            docs.push(text);
+        }
+    }
+    Ok(docs.join("\n\n"))
+}
+
+/// Returns the text of the outer doc comments (and `#[doc = "..."]` attributes) of the
+/// specified item, with the common leading indentation removed.
+fn doc_comment_text(item: TokenStream2) -> Result<String> {
+    let attrs = (|input: ParseStream| {
+        let attrs = input.call(Attribute::parse_outer)?;
+        input.parse::<TokenStream2>()?;
+        Ok(attrs)
+    })
+    .parse2(item)?;
+    let mut lines: Vec<String> = Vec::new();
+    for attr in attrs {
+        let Meta::NameValue(name_value) = &attr.meta else {
+            continue;
+        };
+        if !name_value.path.is_ident("doc") {
+            continue;
+        }
+        if let Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) = &name_value.value
+        {
+            lines.extend(lit.value().split('\n').map(String::from));
+        }
     }
+    let text = fix_indentation(lines.join("\n"));
+    Ok(text.trim_matches('\n').to_string())
 }
 
-/// Used to mark an entity within a piece of source code. Used with [`CompressedString`].
-#[derive(Clone, PartialEq, Eq)]
-struct SourceEntity {
-    start: usize,
-    end: usize,
+/// Internal implementation behind [`macro@embed_docs`].
+fn embed_docs_internal(tokens: impl Into<TokenStream2>) -> Result<TokenStream2> {
+    let output = embed_docs_internal_str(tokens)?;
+    Ok(quote!(#output))
 }
 
-impl SourceEntity {
-    pub fn new(start: usize, end: usize) -> SourceEntity {
-        SourceEntity { start, end }
-    }
-
-    /// Marks the character positions corresponding with this entity as belonging to this
-    /// entity in the enclosing [`CompressedString`].
-    pub fn claim(&self, claimed: &mut Vec<bool>) {
-        for i in self.start..min(self.end, claimed.len()) {
-            claimed[i] = true;
+/// Inner version of [`embed_dir_internal`] that just returns the result as a [`String`].
+fn embed_dir_internal_str(tokens: impl Into<TokenStream2>) -> Result<String> {
+    let dir_args = parse2::<EmbedDirArgs>(tokens.into())?;
+    let mut recursive = false;
+    let mut options = Vec::new();
+    for option in dir_args.options {
+        match option.name == "recursive" {
+            true => recursive = option.flag()?,
+            false => options.push(option),
         }
     }
-
-    /// Returns `true` if this entity already appears in the specified claimed vec
-    pub fn is_claimed(&self, claimed: &Vec<bool>) -> bool {
-        claimed[(self.start + self.end) / 2]
-    }
-
-    // pub fn value<'a>(&self, source: &'a String) -> &'a str {
-    //     &source.as_str()[self.start..self.end]
-    // }
-
-    // pub fn contains(&self, x: usize) -> bool {
-    //     x >= self.start && x < self.end
-    // }
+    let dir = dir_args.dir_path.value();
+    let dir = glob::Pattern::escape(dir.trim_end_matches('/'));
+    let pattern = match recursive {
+        true => format!("{}/**/*.rs", dir),
+        false => format!("{}/*.rs", dir),
+    };
+    let args = EmbedArgs {
+        file_path: LitStr::new(&pattern, dir_args.dir_path.span()),
+        file_paths: Vec::new(),
+        target: None,
+        targets: Vec::new(),
+        options,
+    };
+    embed_with(&args, MarkdownLanguage::Ignore, |options| {
+        if let (false, Some((_, file_paths))) = (cfg!(test), source_files(&args, options)?) {
+            for file_path in file_paths {
+                write_green(DOCIFYING);
+                println!("{}", prettify_path(&file_path).display());
+            }
+        }
+        embed_codes(&args, options, false)
+    })
 }
 
-/// Represents a [`String`] that has been compressed in some way, and includes data structures
-/// allowing us to map individual characters back to their original positions in the
-/// uncompressed version of the [`String`].
-struct CompressedString {
-    chars: HashMap<usize, OffsetChar>,
-    chars_arr: Vec<OffsetChar>,
+/// Internal implementation behind [`macro@embed_dir`].
+fn embed_dir_internal(tokens: impl Into<TokenStream2>) -> Result<TokenStream2> {
+    let output = embed_dir_internal_str(tokens)?;
+    Ok(quote!(#output))
 }
 
-impl CompressedString {
-    fn to_string(&self) -> String {
-        self.chars_arr.iter().map(|c| c.char).collect()
-    }
+/// Returns every name exported from the rust source file at the specified path, in the order
+/// they appear. See [`macro@list_exports`].
+fn file_exports(file_path: &Path) -> Result<Vec<Ident>> {
+    let source_file = load_source_file(file_path)
+        .map_err(|err| Error::new(Span::call_site(), err.message(file_path, None)))?;
+    let parsed = parse_source_file(&source_file.source, file_path)?;
+    let mut visitor = ItemVisitor::new(ItemSearch::Nothing, None);
+    visitor.visit_file(&parsed);
+    malformed_exports_error(&quote!(), file_path, visitor.errors)?;
+    Ok(visitor.candidates)
 }
 
-static DOCIFY_ATTRIBUTES: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"\n?\#\[(?:\w+\s*::\s*)*(?:export|export_content)(?:\s*\(\s*(\w+)\s*\))?\]\n?")
-        .unwrap()
-});
+/// Internal implementation behind [`macro@list_exports`].
+fn list_exports_internal(tokens: impl Into<TokenStream2>) -> Result<TokenStream2> {
+    let file_path = parse2::<LitStr>(tokens.into())?;
+    // return blank result if we can't properly resolve the root directory
+    let Some(root) = manifest_dir() else {
+        return Ok(quote!([]));
+    };
+    let names = file_exports(&root.join(file_path.value()))
+        .map_err(|err| Error::new(file_path.span(), err.to_string()))?
+        .iter()
+        .map(|ident| ident.unraw().to_string())
+        .collect::<Vec<String>>();
+    Ok(quote!([#(#names),*]))
+}
 
-static DOC_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"///.*").unwrap());
-static DOC_COMMENT_ATTR: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r#"#\[doc\s*=\s*".*"\s*]"#).unwrap());
-static LINE_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"//.*").unwrap());
-static MULTI_LINE_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"/\*[\s\S]*?\*/").unwrap());
-static HTML_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"<!--[\s\S]*?-->").unwrap());
-static MARKDOWN_CODEBLOCK: Lazy<Regex> = Lazy::new(|| Regex::new(r"```[\s\S]*?```").unwrap());
-// static ref STRING_LIT: Regex = Regex::new(r#"("([^"\\]|\\[\s\S])*")"#).unwrap();
+/// Returns the paths of the files with one of the specified extensions inside `dir` or any of
+/// its subdirectories, in sorted order.
+fn files_with_extensions(dir: &Path, extensions: &[&str]) -> Vec<PathBuf> {
+    let mut paths = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(std::result::Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            path.extension().is_some_and(|ext| {
+                extensions
+                    .iter()
+                    .any(|extension| ext.eq_ignore_ascii_case(extension))
+            })
+        })
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+    paths
+}
 
-impl From<&String> for CompressedString {
-    fn from(value: &String) -> Self {
-        let mut entities: Vec<SourceEntity> = Vec::new();
-        let mut claimed: Vec<bool> = vec![false; value.len()];
-        for m in DOC_COMMENT.find_iter(value) {
-            let entity = SourceEntity::new(m.start(), m.end());
-            entity.claim(&mut claimed);
-            entities.push(entity);
-        }
-        for m in DOC_COMMENT_ATTR.find_iter(value) {
-            let entity = SourceEntity::new(m.start(), m.end());
-            if !entity.is_claimed(&claimed) {
-                entity.claim(&mut claimed);
-                entities.push(entity);
-            }
-        }
-        for m in MULTI_LINE_COMMENT.find_iter(value) {
-            let entity = SourceEntity::new(m.start(), m.end());
-            if !entity.is_claimed(&claimed) {
-                entity.claim(&mut claimed);
-                entities.push(entity);
-            }
-        }
-        for m in LINE_COMMENT.find_iter(value) {
-            let entity = SourceEntity::new(m.start(), m.end());
-            if !entity.is_claimed(&claimed) {
-                entity.claim(&mut claimed);
-                entities.push(entity);
-            }
-        }
-        for m in DOCIFY_ATTRIBUTES.find_iter(value) {
-            let entity = SourceEntity::new(m.start(), m.end());
-            if !entity.is_claimed(&claimed) {
-                entity.claim(&mut claimed);
-                entities.push(entity);
+/// Returns the arguments of every `docify::embed!(..)`-style call (other than
+/// `docify::embed_dir!(..)`) found in the specified text, skipping any that don't parse, such
+/// as the placeholder `docify::embed!(..)` in prose. For `docify::embed_diff!(..)`, the old
+/// source path is included as an extra [`EmbedArgs`] with the same target.
+fn embed_calls(text: &str) -> Vec<EmbedArgs> {
+    let mut calls = Vec::new();
+    for m in EMBED_CALL.find_iter(text) {
+        let mut depth = 1;
+        let Some(len) = text[m.end()..].find(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
             }
-        }
-        let mut compressed = CompressedString {
-            chars_arr: Vec::new(),
-            chars: HashMap::new(),
+            depth == 0
+        }) else {
+            continue;
         };
-        let mut cursor = 0;
-        let mut byte_index = 0;
-        while byte_index < value.len() {
-            let current_char = &value[byte_index..].chars().next().unwrap(); // get the current character
-            let char_len = current_char.len_utf8(); // get its length in bytes
-
-            if claimed[byte_index] || current_char.is_whitespace() {
-                byte_index += char_len;
-                continue;
+        let Ok(tokens) = text[m.end()..(m.end() + len)].parse::<TokenStream2>() else {
+            continue;
+        };
+        if m.as_str().contains("embed_diff") {
+            if let Ok(diff) = parse2::<EmbedDiffArgs>(tokens) {
+                calls.push(EmbedArgs {
+                    file_path: diff.old_file_path,
+                    ..diff.new_args.clone()
+                });
+                calls.push(diff.new_args);
             }
-            let oc = OffsetChar::new(*current_char, byte_index);
-            compressed.chars.insert(cursor, oc);
-            compressed.chars_arr.push(oc);
-            cursor += 1;
-            byte_index += char_len;
+        } else if let Ok(args) = parse2::<EmbedArgs>(tokens) {
+            calls.push(args);
         }
-
-        compressed
     }
+    calls
 }
 
-/// Responsible for retrieving the "contents" of an item, used by `#[docify::export_contents]`
-fn get_content_tokens<'a>(item: &'a Item) -> TokenStream2 {
-    match item {
-        // Item::Const(item_const) => item_const.to_token_stream(),
-        // Item::Enum(item_enum) => item_enum.to_token_stream(),
-        // Item::ExternCrate(item_extern) => item_extern.to_token_stream(),
-        Item::Fn(item_fn) => {
-            let mut tokens = TokenStream2::new();
-            tokens.extend(item_fn.block.stmts.iter().map(|t| t.to_token_stream()));
-            tokens
-        }
-        Item::ForeignMod(item_mod) => {
-            let mut tokens = TokenStream2::new();
-            tokens.extend(item_mod.items.iter().map(|t| t.to_token_stream()));
-            tokens
-        }
-        Item::Impl(item_impl) => {
-            let mut tokens = TokenStream2::new();
-            tokens.extend(item_impl.items.iter().map(|t| t.to_token_stream()));
-            tokens
-        }
-        // Item::Macro(item_macro) => item_macro.to_token_stream(),
-        Item::Mod(item_mod) => {
-            let Some(content) = &item_mod.content else {
-                return item_mod.to_token_stream();
+/// Returns the exports declared in the `.rs` files in `src_dir` that are never embedded by the
+/// `.rs` and `.md` files in `doc_dir`, along with the file each is declared in. Paths embedded
+/// from are resolved against `root`. See [`macro@assert_all_embedded`].
+fn find_unused_exports(
+    root: &Path,
+    src_dir: &Path,
+    doc_dir: &Path,
+) -> Result<Vec<(PathBuf, Ident)>> {
+    let mut embedded_names: HashSet<String> = HashSet::new();
+    let mut embedded_files: HashSet<PathBuf> = HashSet::new();
+    for doc_path in files_with_extensions(doc_dir, &["rs", "md"]) {
+        let Ok(doc) = load_source_file(&doc_path) else {
+            continue;
+        };
+        for args in embed_calls(&doc.source) {
+            let targets = args.target.iter().chain(args.targets.iter());
+            let mut named = false;
+            for target in targets {
+                match target {
+                    EmbedTarget::Item(ident) => embedded_names.insert(ident.unraw().to_string()),
+                    // any segment may be the export, i.e. `MyType` in `MyType::method`
+                    EmbedTarget::Path(path) => {
+                        for seg in &path.segments {
+                            embedded_names.insert(seg.ident.unraw().to_string());
+                        }
+                        true
+                    }
+                    EmbedTarget::Lines(_) => continue,
+                };
+                named = true;
+            }
+            if named {
+                continue;
+            }
+            // the whole file (or a part of it not tied to any one export) is embedded
+            let workspace_relative = args
+                .options
+                .iter()
+                .any(|option| option.name == "workspace_relative");
+            let call_root = match workspace_relative {
+                true => workspace_root().unwrap_or_else(|| root.to_path_buf()),
+                false => root.to_path_buf(),
             };
-            let mut tokens = TokenStream2::new();
-            tokens.extend(content.1.iter().map(|t| t.to_token_stream()));
-            tokens
+            let file_paths = match args.file_paths.is_empty() {
+                true => vec![args.file_path.clone()],
+                false => args.file_paths.clone(),
+            };
+            for file_path in file_paths {
+                for path in resolve_source_paths(&call_root, &file_path).unwrap_or_default() {
+                    if let Ok(path) = fs::canonicalize(path) {
+                        embedded_files.insert(path);
+                    }
+                }
+            }
         }
-        // Item::Static(item_static) => item_static.to_token_stream(),
-        // Item::Struct(item_struct) => item_struct.to_token_stream(),
-        Item::Trait(item_trait) => {
-            let mut tokens = TokenStream2::new();
-            tokens.extend(item_trait.items.iter().map(|t| t.to_token_stream()));
-            tokens
+    }
+    let mut unused = Vec::new();
+    for src_path in files_with_extensions(src_dir, &["rs"]) {
+        if fs::canonicalize(&src_path).is_ok_and(|path| embedded_files.contains(&path)) {
+            continue;
+        }
+        for ident in file_exports(&src_path)? {
+            if !embedded_names.contains(&ident.unraw().to_string()) {
+                unused.push((src_path.clone(), ident));
+            }
         }
-        Item::TraitAlias(item_trait_alias) => item_trait_alias.to_token_stream(),
-        // Item::Type(item_type) => item_type.to_token_stream(),
-        // Item::Union(item_union) => item_union.to_token_stream(),
-        // Item::Use(item_use) => item_use.to_token_stream(),
-        // Item::Verbatim(item_verbatim) => item_verbatim.to_token_stream(),
-        _ => item.to_token_stream(),
     }
+    Ok(unused)
 }
 
-/// Finds and returns the specified [`Item`] within a source text string and returns the exact
-/// source code of that item, without any formatting changes. If span locations are stabilized,
-/// this can be removed along with most of the [`CompressedString`] machinery.
-fn source_excerpt<'a, T: ToTokens>(
-    source: &'a String,
-    item: &'a T,
-    style: ResultStyle,
-) -> Result<String> {
-    // note: can't rely on span locations because this requires nightly and/or is otherwise
-    // bugged
-    let compressed_source = CompressedString::from(source);
-    let item_tokens = match style {
-        ResultStyle::Export => item.to_token_stream(),
-        ResultStyle::ExportContent => get_content_tokens(&parse2::<Item>(item.to_token_stream())?),
-    };
-    let compressed_item = CompressedString::from(&item_tokens.to_string());
-    let compressed_source_string = compressed_source.to_string();
-    let compressed_item_string = compressed_item.to_string();
-    let Some(found_start) = compressed_source_string.find(compressed_item_string.as_str()) else {
-        return Err(Error::new(
-            item.span(),
-            "You have found a bug in docify! Please submit a new GitHub issue at \
-            https://github.com/sam0x17/docify/issues/new?title=%60source_excerpt\
-            %60%3A%20can%27t%20find%20item%20in%20source with a sample of the item \
-            you are trying to embed.",
-        ));
-    };
-    let start_c = compressed_source.chars[&found_start];
-    let start_pos = start_c.original_pos;
-    let start_pos = line_start_position(source, start_pos);
-    let end_c = compressed_source.chars[&(found_start + compressed_item_string.len() - 1)];
-    let end_pos = end_c.original_pos;
-    let final_excerpt = &source[start_pos..min(end_pos + 1, source.len())];
-    Ok(final_excerpt
-        .lines()
-        .filter(|line| !(DOCIFY_ATTRIBUTES.is_match(line) && !line.trim().starts_with("//")))
-        .collect::<Vec<&str>>()
-        .join("\n"))
-}
-
-/// Inner version of [`embed_internal`] that just returns the result as a [`String`].
-fn embed_internal_str(tokens: impl Into<TokenStream2>, lang: MarkdownLanguage) -> Result<String> {
-    let args: EmbedArgs = parse2::<EmbedArgs>(tokens.into())?;
-    println!(
-        "embed_internal_str ----> args: file_path: {}, item_ident: {:?}",
-        args.file_path.value(),
-        args.item_ident.as_ref().map(|i| i.to_string())
-    );
-    // return blank result if we can't properly resolve `caller_crate_root`
-    let Some(root) = caller_crate_root() else {
-        println!("embed_internal_str ----> Failed to resolve caller_crate_root");
-        return Ok(String::from(""));
+/// Internal implementation behind [`macro@assert_all_embedded`].
+fn assert_all_embedded_internal(tokens: impl Into<TokenStream2>) -> Result<TokenStream2> {
+    let args = parse2::<AssertAllEmbeddedArgs>(tokens.into())?;
+    // return blank result if we can't properly resolve the root directory
+    let Some(root) = manifest_dir() else {
+        return Ok(quote!());
     };
-    println!("embed_internal_str ----> Root resolved: {:?}", root);
-    let file_path = root.join(args.file_path.value());
-    println!("embed_internal_str ----> File path: {:?}", file_path);
-    let source_code = match fs::read_to_string(&file_path) {
-        Ok(src) => {
-            println!("embed_internal_str ----> Successfully read source file");
-            println!(
-                "embed_internal_str ----> Source code of the entire file: {}",
-                src
-            );
-            src
-        }
-        Err(e) => {
-            println!(
-                "embed_internal_str ----> Failed to read source file: {:?}",
-                e
-            );
+    for dir in [&args.src_dir, &args.doc_dir] {
+        if !root.join(dir.value()).is_dir() {
             return Err(Error::new(
-                args.file_path.span(),
-                format!(
-                    "Could not read the specified path '{}'.",
-                    file_path.display(),
-                ),
+                dir.span(),
+                format!("Could not find the directory '{}'.", dir.value()),
             ));
         }
-    };
-    let parsed = source_code.parse::<TokenStream2>()?;
-    println!("embed_internal_str ----> Parsed source code successfully");
-    println!(
-        "embed_internal_str ----> Parsed source code: removed comments and other cleanups {}",
-        parsed
-    );
-    let source_file: File = parse2::<File>(parsed)?;
-    println!("embed_internal_str ----> Parsed source file successfully");
-    println!(
-        "embed_internal_str ----> Source file items count: {}",
-        source_file.items.len()
-    );
-    println!(
-        "embed_internal_str ----> Source file attributes count: {}",
-        source_file.attrs.len()
-    );
-    if let Some(shebang) = &source_file.shebang {
-        println!(
-            "embed_internal_str ----> Source file has shebang: {}",
-            shebang
-        );
     }
-
-    let output = if let Some(ident) = args.item_ident {
-        println!("embed_internal_str ----> Searching for item: {}", ident);
-        let mut visitor = ItemVisitor {
-            search: ident.clone(),
-            results: Vec::new(),
-        };
-        visitor.visit_file(&source_file);
-        println!(
-            "embed_internal_str ----> Visitor results: {:?}",
-            visitor.results
-        );
-        if visitor.results.is_empty() {
-            println!(
-                "embed_internal_str ----> No results found for item: {}",
-                ident
-            );
-            return Err(Error::new(
-                ident.span(),
-                format!(
-                    "Could not find docify export item '{}' in '{}'.",
-                    ident,
-                    file_path.display(),
-                ),
-            ));
-        }
-        let mut results: Vec<String> = Vec::new();
-        for (item, style) in visitor.results {
-            println!(
-                "embed_internal_str ----> Processing item with style: {:?}",
-                style
-            );
-            let excerpt = source_excerpt(&source_code, &item, style)?;
-            println!("embed_internal_str ----> Excerpt: {}", excerpt);
-            let formatted = fix_indentation(excerpt);
-            println!("embed_internal_str ----> Formatted: {}", formatted);
-            let example = into_example(formatted.as_str(), lang);
-            println!("embed_internal_str ----> Example: {}", example);
-            results.push(example);
-        }
-        results.join("\n")
-    } else {
-        println!("embed_internal_str ----> No specific item requested, using entire source");
-        into_example(source_code.as_str(), lang)
-    };
-    println!(
-        "embed_internal_str ----> Final output length: {}",
-        output.len()
-    );
-    Ok(output)
+    let unused = find_unused_exports(
+        &root,
+        &root.join(args.src_dir.value()),
+        &root.join(args.doc_dir.value()),
+    )
+    .map_err(|err| Error::new(args.src_dir.span(), err.to_string()))?;
+    if unused.is_empty() {
+        return Ok(quote!());
+    }
+    let unused = unused
+        .iter()
+        .map(|(path, ident)| {
+            let path = path.strip_prefix(&root).unwrap_or(path);
+            format!("'{}' in '{}'", ident, path.display())
+        })
+        .collect::<Vec<String>>();
+    Err(Error::new(
+        Span::call_site(),
+        format!(
+            "Found {} docify export(s) that are never embedded in '{}': {}.",
+            unused.len(),
+            args.doc_dir.value(),
+            unused.join(", "),
+        ),
+    ))
 }
 
 /// Internal implementation behind [`macro@embed`].
@@ -1059,6 +6345,41 @@ fn embed_internal(tokens: impl Into<TokenStream2>, lang: MarkdownLanguage) -> Re
     Ok(quote!(#output))
 }
 
+/// Internal implementation behind [`macro@try_embed`].
+fn try_embed_internal(tokens: impl Into<TokenStream2>) -> Result<TokenStream2> {
+    let output = try_embed_internal_str(tokens)?;
+    Ok(quote!(#output))
+}
+
+/// Inner version of [`try_embed_internal`] that just returns the result as a [`String`].
+fn try_embed_internal_str(tokens: impl Into<TokenStream2>) -> Result<String> {
+    let args: EmbedArgs = parse2::<EmbedArgs>(tokens.into())?;
+    let lang = MarkdownLanguage::Ignore;
+    let err = match embed_with(&args, lang, |options| embed_codes(&args, options, false)) {
+        Ok(output) => return Ok(output),
+        Err(err) => err,
+    };
+    let targets = args
+        .targets
+        .iter()
+        .chain(&args.target)
+        .map(|target| target.name())
+        .collect::<Vec<String>>();
+    let what = match targets.is_empty() {
+        true => format!("'{}'", args.file_path.value()),
+        false => format!("'{}' from '{}'", targets.join(", "), args.file_path.value()),
+    };
+    if !cfg!(test) {
+        write_yellow(DOCIFY_WARNING);
+        println!(
+            "could not embed {}, embedding a placeholder instead: {}",
+            what, err
+        );
+    }
+    let placeholder = format!("// [docify: could not embed {}]", what);
+    Ok(into_fenced_example(&placeholder, lang.fence_info()))
+}
+
 /// Used to parse args for [`macro@compile_markdown`].
 #[derive(Parse)]
 struct CompileMarkdownArgs {
@@ -1103,9 +6424,8 @@ fn compile_markdown_internal(tokens: impl Into<TokenStream2>) -> Result<TokenStr
             if cfg!(not(test)) {
                 write_green(DOCIFYING);
                 println!(
-                    "{} {} {}",
+                    "{} => {}", // TODO: fancy arrow
                     prettify_path(&input_path).display(),
-                    "=>", // TODO: fancy arrow
                     prettify_path(&output).display(),
                 );
             }
@@ -1201,13 +6521,12 @@ fn compile_markdown_dir<P1: AsRef<Path>, P2: AsRef<Path>>(
         })
     {
         let src_path = entry.path();
-        let dest_path = transpose_subpath(&input_dir, &src_path, &output_dir);
+        let dest_path = transpose_subpath(&input_dir, src_path, &output_dir);
         if cfg!(not(test)) {
             write_green(DOCIFYING);
             println!(
-                "{} {} {}",
-                prettify_path(&src_path).display(),
-                "=>", // TODO: fancy arrow
+                "{} => {}", // TODO: fancy arrow
+                prettify_path(src_path).display(),
                 prettify_path(&dest_path).display(),
             );
         }
@@ -1342,6 +6661,15 @@ fn compile_markdown_source<S: AsRef<str>>(source: S) -> Result<String> {
 ///
 /// Another convention we encourage, shown above, is naming template files `foo.docify.md` so
 /// they can exist alongside the generated `foo.md` file without collisions.
+///
+/// Because `docify_macros` is a proc-macro crate, it can only export macros, so there is no
+/// function-style API for calling docify from a `build.rs` or other tooling. Generating a file
+/// such as a `README.md` is instead done by a feature-gated `compile_markdown!` call as above,
+/// which writes its output whenever the crate is documented with that feature enabled. For the
+/// same reason, embedded code can't be passed through a closure of your own before it is
+/// written, so one-off transforms such as masking secrets are best applied to the generated
+/// file by whatever step consumes it, or to the example source itself using
+/// `// docify:redact-start` regions (see [`docify::embed!(..)`](`macro@embed`)).
 #[proc_macro]
 pub fn compile_markdown(tokens: TokenStream) -> TokenStream {
     match compile_markdown_internal(tokens) {
@@ -23,6 +23,13 @@ fn test_export_basic_parsing_valid() {
         ),
     )
     .unwrap();
+    export_internal(
+        quote!(some_ident, some_alias),
+        quote!(
+            struct SomeStruct;
+        ),
+    )
+    .unwrap();
 }
 
 #[test]
@@ -192,4 +199,2681 @@ fn bar() {
 }
 "#;
     assert_eq!(fix_leading_indentation(input), output);
+    assert_eq!(
+        fix_leading_indentation(
+            "    a
+  
+    b"
+        ),
+        "a
+
+b"
+    );
+}
+
+#[test]
+fn test_embed_line_range() {
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/file.rs", 2..5), MarkdownLanguage::Ignore).unwrap(),
+        "```rust,ignore\nfn some_fn() {\n    println!(\"foo\");\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/file.rs", 7..=7), MarkdownLanguage::Blank).unwrap(),
+        "```rust\nfn some_other_fn() {\n```"
+    );
+    // line ranges don't require the file to be valid rust
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/file_1.md", 1..2), MarkdownLanguage::Blank).unwrap(),
+        "```rust\n# This is a markdown file\n```"
+    );
+}
+
+#[test]
+fn test_embed_line_range_invalid() {
+    assert!(
+        embed_internal_str(quote!("fixtures/file.rs", 0..3), MarkdownLanguage::Ignore).is_err()
+    );
+    assert!(
+        embed_internal_str(quote!("fixtures/file.rs", 3..3), MarkdownLanguage::Ignore).is_err()
+    );
+    assert!(
+        embed_internal_str(quote!("fixtures/file.rs", 5..2), MarkdownLanguage::Ignore).is_err()
+    );
+    assert!(
+        embed_internal_str(quote!("fixtures/file.rs", 9..=10), MarkdownLanguage::Ignore).is_err()
+    );
+    assert!(
+        embed_internal_str(quote!("fixtures/file.rs", 1..=9), MarkdownLanguage::Ignore).is_ok()
+    );
+}
+
+#[test]
+fn test_embed_statements() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/statements.rs", create_client),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nlet client = Client::new(\"localhost\");\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/statements.rs", config),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nlet config: Config = Config::default();\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/statements.rs", connect),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nclient.connect(&config);\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/statements.rs", println),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nprintln!(\"connected!\");\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/statements.rs", unrelated),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_preserves_formatting() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/formatting.rs", aligned),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n\
+        fn aligned() {\n    \
+            let short       = 1;\n    \
+            let much_longer = 2;\n\
+            \n    \
+            //  +--------+\n    \
+            //  | boxes! |\n    \
+            //  +--------+\n    \
+            let total = short\n              \
+                    + much_longer;\n\
+        }\n\
+        ```"
+    );
+}
+
+#[test]
+fn test_embed_preserves_comments() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/comments.rs", commented),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n\
+        fn commented() -> i32 {\n    \
+            // explanatory comment\n    \
+            let x = 5; // trailing comment\n    \
+            /* block comment */\n    \
+            x + 1 /* inline */\n\
+        } // end of commented\n\
+        ```"
+    );
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/comments.rs", y), MarkdownLanguage::Ignore).unwrap(),
+        "```rust,ignore\nlet y = 7; /* trailing block comment */\n```"
+    );
+}
+
+#[test]
+fn test_embed_item_named_like_flag() {
+    let output = embed_internal_str(
+        quote!("fixtures/flag_names.rs", r#unique),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap();
+    assert!(output.contains("fn unique() {}"));
+    assert!(!output.contains("fn other() {}"));
+    let err = embed_internal_str(
+        quote!("fixtures/flag_names.rs", unique),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("exports an item named `unique`"));
+    assert!(err.contains("`r#unique`"));
+    let err = embed_internal_str(
+        quote!("fixtures/flag_names.rs", unique, dedent, raw),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("exports an item named `unique`"));
+    assert!(embed_internal_str(
+        quote!("fixtures/flag_names.rs", dedent, unique),
+        MarkdownLanguage::Ignore
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_embed_repeated_option() {
+    let err = embed_internal_str(
+        quote!("fixtures/flag_names.rs", dedent, dedent),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("The `dedent` option is specified more than once."));
+    let err = embed_internal_str(
+        quote!("fixtures/flag_names.rs", fence = "text", fence = "rust"),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("The `fence` option is specified more than once."));
+}
+
+#[test]
+fn test_embed_unique() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/duplicates.rs", Duplicate),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nstruct StructOne;\n```\n```rust,ignore\nstruct StructTwo;\n```"
+    );
+    let err = embed_internal_str(
+        quote!("fixtures/duplicates.rs", Duplicate, unique),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("on lines 2, 6"));
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/duplicates.rs", Single, unique),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nstruct Single;\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/duplicates.rs", Single, unique = "yes"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+    assert!(embed_internal_str(
+        quote!("fixtures/duplicates.rs", Single, not_an_option),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_glob() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/tutorial/tutorial_*.rs"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn step_one() {\n    println!(\"one\");\n}\n```\n\
+        ```rust,ignore\nfn step_two() {\n    println!(\"two\");\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/tutorial/tutorial_?.rs", 2..3),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\n    println!(\"one\");\n```\n```rust\n    println!(\"two\");\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/tutorial/missing_*.rs"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+    assert!(
+        embed_internal_str(quote!("fixtures/tutorial/[.rs"), MarkdownLanguage::Ignore).is_err()
+    );
+}
+
+#[test]
+fn test_embed_workspace_relative() {
+    let crate_relative = embed_internal_str(
+        quote!("fixtures/file.rs", some_fn),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap();
+    let workspace_relative = embed_internal_str(
+        quote!("macros/fixtures/file.rs", some_fn, workspace_relative),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap();
+    assert_eq!(crate_relative, workspace_relative);
+    assert!(embed_internal_str(
+        quote!("fixtures/file.rs", some_fn, workspace_relative),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_path_env() {
+    let crate_relative = embed_internal_str(
+        quote!("fixtures/file.rs", some_fn),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap();
+    let env_relative = embed_internal_str(
+        quote!("fixtures/file.rs", some_fn, path_env = "CARGO_MANIFEST_DIR"),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap();
+    assert_eq!(crate_relative, env_relative);
+    let err = embed_internal_str(
+        quote!("gen.rs", some_fn, path_env = "DOCIFY_UNSET_TEST_VAR"),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("`DOCIFY_UNSET_TEST_VAR` specified by the `path_env` option is not set"));
+    let err = embed_internal_str(
+        quote!("missing.rs", some_fn, path_env = "CARGO_MANIFEST_DIR"),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("does not exist"));
+    assert!(err.contains("as specified by the `path_env` option"));
+    assert!(!err.contains("workspace_relative"));
+    assert!(embed_internal_str(
+        quote!(
+            "fixtures/file.rs",
+            some_fn,
+            path_env = "CARGO_MANIFEST_DIR",
+            workspace_relative
+        ),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_show_source() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/file.rs", some_other_fn, show_source),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn some_other_fn() {\n    println!(\"bar\");\n}\n\
+        // from fixtures/file.rs:7\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/file.rs", 3..4, show_source),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n    println!(\"foo\");\n// from fixtures/file.rs:3\n```"
+    );
+}
+
+#[test]
+fn test_embed_impl_method() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/impls.rs", MyType::my_method),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn my_method(&self) -> u32 {\n    42\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/impls.rs", MyType::fmt),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n\
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n    \
+            write!(f, \"MyType\")\n\
+        }\n\
+        ```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/impls.rs", Wrapper::unwrap),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn unwrap(self) -> T {\n    self.0\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/impls.rs", MyType::MAX),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nconst MAX: u32 = 100;\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/impls.rs", MyType::Item),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\ntype Item = u32;\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/impls.rs", MyType::missing),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+    assert!(embed_internal_str(
+        quote!("fixtures/impls.rs", Other::my_method),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_strips_nested_exports() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/nested.rs", outer),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n\
+        mod outer {\n    \
+            fn first() {}\n\
+            \n    \
+            fn second() {}\n\
+            \n    \
+            /// mentions `#[docify::export]` in its docs\n    \
+            fn third() {}\n\
+            \n    \
+            mod inner {\n        \
+                #[test]\n        \
+                fn fourth() {}\n    \
+            }\n\
+        }\n\
+        ```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/nested.rs", second_fn),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn second() {}\n```"
+    );
+}
+
+#[test]
+fn test_embed_after_unicode() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/unicode.rs", after_unicode),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn after_unicode() {\n    println!(\"{}\", GREETING);\n}\n```"
+    );
+}
+
+#[test]
+fn test_embed_dedent() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/formatting.rs", 4..7),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\n        let short       = 1;\n        let much_longer = 2;\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/formatting.rs", 4..8, dedent),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nlet short       = 1;\nlet much_longer = 2;\n\n//  +--------+\n```"
+    );
+}
+
+#[test]
+fn test_embed_non_rust_file() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/config.toml", lang = "toml"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```toml\n[package]\nname = \"example\"\nversion = \"0.1.0\"\n\n[dependencies]\ndocify = \"0.2\"\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/config.toml", 5..=6, lang = "toml"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```toml\n[dependencies]\ndocify = \"0.2\"\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/config.toml", package, lang = "toml"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+    assert!(embed_internal_str(quote!("fixtures/config.toml"), MarkdownLanguage::Ignore).is_err());
+}
+
+#[test]
+fn test_source_file_cache() {
+    let file = load_source_file(Path::new("fixtures/file.rs")).unwrap();
+    let aliased = load_source_file(Path::new("fixtures/../fixtures/./file.rs")).unwrap();
+    assert!(Rc::ptr_eq(&file, &aliased));
+    assert!(std::ptr::eq(file.compressed(), aliased.compressed()));
+    for _ in 0..10 {
+        embed_internal_str(
+            quote!("fixtures/file.rs", some_fn),
+            MarkdownLanguage::Ignore,
+        )
+        .unwrap();
+    }
+    assert!(Rc::ptr_eq(
+        &file,
+        &load_source_file(Path::new("fixtures/file.rs")).unwrap()
+    ));
+}
+
+#[test]
+fn test_source_file_cache_invalidation() {
+    let path = std::env::temp_dir().join(format!("docify-cache-{}.rs", std::process::id()));
+    fs::write(&path, "fn a() {}").unwrap();
+    let file = load_source_file(&path).unwrap();
+    assert!(Rc::ptr_eq(&file, &load_source_file(&path).unwrap()));
+    fs::write(&path, "fn a() {}\nfn b() {}").unwrap();
+    let edited = load_source_file(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(edited.source, "fn a() {}\nfn b() {}");
+}
+
+#[test]
+fn test_embed_no_run() {
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/file.rs", some_fn), MarkdownLanguage::NoRun).unwrap(),
+        "```rust,no_run\nfn some_fn() {\n    println!(\"foo\");\n}\n```"
+    );
+}
+
+#[test]
+fn test_embed_export_aliases() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/aliases.rs", setup_example),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nfn initialize() {\n    println!(\"ready\");\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/aliases.rs", setup),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nfn initialize() {\n    println!(\"ready\");\n}\n```\n\
+        ```rust\nfn other_setup() {\n    println!(\"also ready\");\n}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/aliases.rs", initialize),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_module_path() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/modules.rs", client::run),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\npub fn run() {\n    println!(\"client\");\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/modules.rs", server::nested::run),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\npub fn run() {\n    println!(\"nested\");\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/modules.rs", client::Client::connect),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\npub fn connect(&self) {}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/modules.rs", run), MarkdownLanguage::Blank)
+            .unwrap()
+            .matches("pub fn run()")
+            .count(),
+        3
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/modules.rs", nested::run),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_signature_only() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/signatures.rs", generic_fn, signature_only),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n#[inline]\npub fn generic_fn<T: Clone>(value: &T, count: usize) -> Vec<T>\n\
+        where\n    T: std::fmt::Debug;\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/signatures.rs", greet, signature_only),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn greet(&self) -> String;\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/signatures.rs", NotAFunction, signature_only),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_hidden_lines() {
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/hidden.rs", long_example), MarkdownLanguage::Blank)
+            .unwrap(),
+        "```rust\nfn long_example() {\n    let client = 1;\n    // ...\n    println!(\"{}\", client);\n}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/hidden.rs", unterminated),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+    assert_eq!(
+        HIDDEN_REGION
+            .replace("a\n// docify:hide-start\nb\n// docify:hide-end\nc")
+            .unwrap(),
+        "a\n// ...\nc"
+    );
+    assert!(HIDDEN_REGION.replace("a\n// docify:hide-end").is_err());
+}
+
+#[test]
+fn test_embed_respect_cfg() {
+    std::env::set_var("CARGO_FEATURE_DOCIFY_TEST_ENABLED", "1");
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/cfg.rs", example), MarkdownLanguage::Blank)
+            .unwrap()
+            .matches("fn ")
+            .count(),
+        4
+    );
+    let output = embed_internal_str(
+        quote!("fixtures/cfg.rs", example, respect_cfg),
+        MarkdownLanguage::Blank,
+    )
+    .unwrap();
+    assert!(output.contains("fn enabled() {}"));
+    assert!(output.contains("fn unknown() {}"));
+    assert!(!output.contains("fn disabled() {}"));
+    assert!(!output.contains("fn nested() {}"));
+}
+
+#[test]
+fn test_embed_redacted_lines() {
+    let output = embed_internal_str(
+        quote!("fixtures/redacted.rs", connect),
+        MarkdownLanguage::Blank,
+    )
+    .unwrap();
+    assert_eq!(
+        output,
+        "```rust\nfn connect() {\n    /* redacted */\n    let client = Client::new(API_KEY);\n}\n```"
+    );
+    assert!(!output.contains("sk-live"));
+}
+
+#[test]
+fn test_embed_read_errors() {
+    let err = embed_internal_str(quote!("fixtures/latin1.rs"), MarkdownLanguage::Ignore)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("is not valid UTF-8 (invalid byte at offset 6)"));
+    let err = embed_internal_str(quote!("fixtures/missing.rs"), MarkdownLanguage::Ignore)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("because it does not exist"));
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let resolved = Path::new(&manifest_dir).join("fixtures/missing.rs");
+    assert!(err.contains(&format!("'{}'", resolved.display())));
+    assert!(err.contains(&format!("CARGO_MANIFEST_DIR ('{}')", manifest_dir)));
+}
+
+#[test]
+fn test_embed_enum_variant() {
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/variants.rs", Message::Data), MarkdownLanguage::Blank)
+            .unwrap(),
+        "```rust\nenum Message {\n    // ...\n    #[allow(unused)]\n    Data {\n        id: u32,\n        \
+        payload: Vec<u8>,\n    },\n    // ...\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/variants.rs", Message::Close),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nenum Message {\n    // ...\n    Close(u16),\n    // ...\n}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/variants.rs", Message::Missing),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_remote_url() {
+    assert!(embed_internal_str(
+        quote!("fixtures/file.rs", some_fn, sha256 = "abc"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+    #[cfg(not(feature = "remote"))]
+    assert!(embed_internal_str(
+        quote!("https://example.com/file.rs", some_fn),
+        MarkdownLanguage::Ignore
+    )
+    .unwrap_err()
+    .to_string()
+    .contains("requires the `remote` feature"));
+    #[cfg(feature = "remote")]
+    assert_eq!(
+        sha256_hex(b"test"),
+        "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+    );
+}
+
+#[cfg(feature = "remote")]
+#[test]
+fn test_remote_cache() {
+    let url = "https://example.com/file.rs";
+    let sha256 = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+    let mut cache = RemoteCache {
+        dir: std::env::temp_dir().join(format!("docify-remote-test-{}", std::process::id())),
+        trusted: false,
+    };
+    let oversized = vec![b' '; REMOTE_SIZE_LIMIT as usize + 1];
+    assert!(cache
+        .store(url, &oversized, None)
+        .unwrap_err()
+        .contains("larger than the limit of 10 MiB"));
+    assert!(cache
+        .store(url, b"tampered", Some(sha256))
+        .unwrap_err()
+        .contains("do not match the pinned `sha256` checksum"));
+    assert_eq!(cache.cached(url, Some(sha256)), None);
+    let path = cache
+        .store(url, b"test", Some(&sha256.to_uppercase()))
+        .unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"test");
+    // an untrusted cache is only reused if the contents match a pinned checksum
+    assert_eq!(cache.cached(url, Some(sha256)), Some(path.clone()));
+    assert_eq!(cache.cached(url, Some("abc")), None);
+    assert_eq!(cache.cached(url, None), None);
+    cache.trusted = true;
+    assert_eq!(cache.cached(url, None), Some(path.clone()));
+    assert_eq!(cache.cached(url, Some("abc")), None);
+    assert_eq!(cache.cached("https://example.com/other.rs", None), None);
+    fs::remove_dir_all(&cache.dir).unwrap();
+}
+
+#[test]
+fn test_embed_custom_fence() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/file.rs", some_fn, fence = "ignore,edition2021"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```ignore,edition2021\nfn some_fn() {\n    println!(\"foo\");\n}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/file.rs", some_fn, fence = "ignore\n```"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_strip_docs() {
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/docs.rs", documented), MarkdownLanguage::Blank)
+            .unwrap(),
+        "```rust\n#[inline]\n/// Adds one.\nfn documented(value: u32) -> u32 {\n    /// A helper.\n    \
+        #[doc = \"More docs.\"]\n    struct Helper;\n    //// Not a doc comment.\n    \
+        // A regular comment.\n    value + 1\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/docs.rs", documented, strip_docs),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\n#[inline]\nfn documented(value: u32) -> u32 {\n    struct Helper;\n    \
+        //// Not a doc comment.\n    // A regular comment.\n    value + 1\n}\n```"
+    );
+}
+
+#[test]
+fn test_embed_marker() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/markers.txt", marker = "setup"),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\n    let config = load();\n        config.apply();\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/markers.txt", marker = "setup-extra", show_source),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nextra setup\n// from fixtures/markers.txt:8\n```"
+    );
+    let err = embed_internal_str(
+        quote!("fixtures/markers.txt", marker = "broken"),
+        MarkdownLanguage::Blank,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("without a matching `docify:end-broken` on line 10"));
+    assert!(embed_internal_str(
+        quote!("fixtures/markers.txt", marker = "missing"),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_line_numbers() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/formatting.rs", 3..=13, line_numbers),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n \
+        1 |     fn aligned() {\n \
+        2 |         let short       = 1;\n \
+        3 |         let much_longer = 2;\n \
+        4 |\n \
+        5 |         //  +--------+\n \
+        6 |         //  | boxes! |\n \
+        7 |         //  +--------+\n \
+        8 |         let total = short\n \
+        9 |                   + much_longer;\n\
+        10 |     }\n\
+        11 | }\n\
+        ```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/file.rs", some_fn, line_numbers),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_single_fence() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/group.rs", counter_example),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap()
+        .matches("```rust,ignore")
+        .count(),
+        3
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/group.rs", counter_example, single_fence),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nstruct Counter {\n    count: u32,\n}\n\nimpl Counter {\n    \
+        fn increment(&mut self) {\n        self.count += 1;\n    }\n}\n\n\
+        fn new_counter() -> Counter {\n    Counter { count: 0 }\n}\n```"
+    );
+}
+
+#[test]
+fn test_export_malformed_names() {
+    let err = export_internal(
+        quote!(123),
+        quote!(
+            struct SomeStruct;
+        ),
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Invalid docify export name `123`, expected an ident"));
+    let err = embed_internal_str(
+        quote!("fixtures/malformed.rs", valid),
+        MarkdownLanguage::Blank,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("Found 2 malformed docify export attribute(s)"));
+    assert!(err.contains("Invalid docify export name `123`"));
+    assert!(err.contains("Empty parentheses are not allowed"));
+    assert!(embed_internal_str(
+        quote!("fixtures/malformed.rs", numeric),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_with_module_path() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/modules.rs", server::nested::run, with_module_path),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nmod server {\n    mod nested {\n        pub fn run() {\n            \
+        println!(\"nested\");\n        }\n    }\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/modules.rs", Client::connect, with_module_path),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nmod client {\n    pub fn connect(&self) {}\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/file.rs", some_fn, with_module_path),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nfn some_fn() {\n    println!(\"foo\");\n}\n```"
+    );
+}
+
+#[test]
+fn test_embed_with_imports() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/imports.rs", count_words, with_imports),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nuse std::collections::HashMap;\nuse std::io::*;\n\n\
+        fn count_words(text: &str) -> HashMap<&str, usize> {\n    \
+        let mut counts = HashMap::new();\n    for word in text.split_whitespace() {\n        \
+        *counts.entry(word).or_insert(0) += 1;\n    }\n    counts\n}\n```"
+    );
+    let output = embed_internal_str(
+        quote!("fixtures/imports.rs", describe, with_imports),
+        MarkdownLanguage::Blank,
+    )
+    .unwrap();
+    assert!(
+        output.starts_with("```rust\nuse std::fmt::{Debug, Display as Show};\nuse std::io::*;\n\n")
+    );
+    assert!(!output.contains("Arc"));
+}
+
+#[test]
+fn test_embed_suggests_near_misses() {
+    let err = embed_internal_str(
+        quote!("fixtures/group.rs", counter_exampel),
+        MarkdownLanguage::Blank,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("Did you mean 'counter_example'?"));
+    let err = embed_internal_str(
+        quote!("fixtures/modules.rs", server::rum),
+        MarkdownLanguage::Blank,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("Did you mean 'run'?"));
+    let err = embed_internal_str(
+        quote!("fixtures/group.rs", something_else),
+        MarkdownLanguage::Blank,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(!err.contains("Did you mean"));
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+}
+
+#[test]
+fn test_embed_trait_signatures_only() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/signatures.rs", Storage, signatures_only),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\npub trait Storage {\n    /// The key type.\n    type Key;\n\n    \
+        fn get(&self, key: &Self::Key) -> Option<String>;\n\n    \
+        // Returns `true` if the key exists.\n    fn contains(&self, key: &Self::Key) -> bool;\n\n    \
+        fn len_where<T>(&self) -> usize\n    where\n        T: Default;\n}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/signatures.rs", generic_fn, signatures_only),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_title() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/file.rs", some_fn, title = "file.rs"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n// file.rs\nfn some_fn() {\n    println!(\"foo\");\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!(
+                "fixtures/file.rs",
+                some_fn,
+                title = "file.rs",
+                title_style = "fence"
+            ),
+            MarkdownLanguage::Rust
+        )
+        .unwrap(),
+        "```rust,title=file.rs\nfn some_fn() {\n    println!(\"foo\");\n}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/file.rs", some_fn, title = "a\nb"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+    assert!(embed_internal_str(
+        quote!(
+            "fixtures/file.rs",
+            some_fn,
+            title = "file.rs",
+            title_style = "above"
+        ),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_macro_rules() {
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/macros.rs", square), MarkdownLanguage::Blank)
+            .unwrap(),
+        "```rust\nmacro_rules! square {\n    ($x:expr) => {\n        $x * $x\n    };\n    \
+        ($x:expr, $($rest:expr),+) => {\n        (square!($x), square!($($rest),+))\n    };\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/macros.rs", max_example),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nmacro_rules! max {\n    ($x:expr) => { $x };\n    ($x:expr, $($y:expr),+) => {{\n        \
+        let rest = max!($($y),+);\n        if $x > rest { $x } else { rest }\n    }};\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/macros.rs", url), MarkdownLanguage::Blank).unwrap(),
+        "```rust\n#[macro_export]\nmacro_rules! url {\n    ($host:literal) => {\n        \
+        concat!(\"https://\", $host) // a scheme with slashes\n    };\n}\n```"
+    );
+    assert!(
+        embed_internal_str(quote!("fixtures/macros.rs", max), MarkdownLanguage::Blank).is_err()
+    );
+}
+
+#[test]
+fn test_literal_positions() {
+    let source = r##"let a = "//"; // c
+let b = '\''; let c = '"'; fn f<'a>(x: &'a str) {}
+let d = r#"/* "# ;"##;
+    let literals = literal_positions(source);
+    let marked = source
+        .char_indices()
+        .filter(|(i, _)| literals[*i])
+        .map(|(_, c)| c)
+        .collect::<String>();
+    assert_eq!(marked, r##""//"'\'''"'r#"/* "#"##);
+}
+
+#[test]
+fn test_embed_max_width() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/wide.rs", wide, max_width = 40),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nfn wide() {\n    /// This doc comment is far too long\n    \
+        /// to fit within a narrow column of\n    /// rendered docs.\n    let value = 1;\n    \
+        // Short comment.\n    \
+        let very_long_line_of_code_that_is_not_wrapped = value + value + value + value + value;\n\
+        }\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/wide.rs", wide, max_width = 0),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+    assert!(embed_internal_str(
+        quote!("fixtures/wide.rs", wide, max_width = "80"),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+    assert_eq!(
+        wrap_comments("// a verylongword b", 5),
+        "// a\n// verylongword\n// b"
+    );
+}
+
+#[test]
+fn test_embed_with_preamble() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/preamble.rs", greet, with_preamble),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\n// Connects to the server and sends a greeting.\n// Retries are handled by the client.\n\
+        fn greet() {\n    println!(\"hello\");\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/preamble.rs", add_one, with_preamble),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\n/*\n * A block comment describing the example.\n */\n/// Adds one.\n\
+        fn add_one(x: u32) -> u32 {\n    x + 1\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/preamble.rs", greet),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nfn greet() {\n    println!(\"hello\");\n}\n```"
+    );
+}
+
+#[test]
+fn test_embed_hidden_items() {
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/hidden_items.rs"), MarkdownLanguage::Blank).unwrap(),
+        "```rust\nfn example() {\n    println!(\"shown\");\n}\n\nstruct Shown;\n\nimpl Shown {\n    \
+        fn visible(&self) {}\n}\n```"
+    );
+    assert!(hide_internal(
+        quote!(),
+        quote!(
+            fn main() {}
+        )
+    )
+    .is_ok());
+    assert!(hide_internal(
+        quote!(something),
+        quote!(
+            fn main() {}
+        )
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_eval_const() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/consts.rs", MAX_SIZE, eval_const),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\npub const MAX_SIZE: usize = 1 << 20; // = 1048576\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/consts.rs", TIMEOUT, eval_const),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nconst TIMEOUT: u64 = (60 * 5 + 30) * 1_000; // in milliseconds\n// = 330000\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/consts.rs", MASK, eval_const),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nstatic MASK: u8 = (0xF0u8 >> 4) as u8 ^ 0b0101; // = 10\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/consts.rs", NAME, eval_const),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+    assert!(embed_internal_str(
+        quote!("fixtures/consts.rs", not_a_const, eval_const),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_diff() {
+    assert_eq!(
+        embed_diff_internal_str(quote!(
+            "fixtures/diff/old.rs",
+            "fixtures/diff/new.rs",
+            setup
+        ))
+        .unwrap(),
+        "```diff\n fn setup() {\n-    let client = Client::new();\n\
+        +    let client = Client::builder().timeout(30).build();\n     \
+        client.connect(\"localhost\");\n }\n```"
+    );
+    assert_eq!(
+        embed_diff_internal_str(quote!(
+            "fixtures/diff/old.rs",
+            "fixtures/diff/new.rs",
+            added
+        ))
+        .unwrap(),
+        "```diff\n+fn added() {}\n```"
+    );
+    assert_eq!(
+        embed_diff_internal_str(quote!(
+            "fixtures/diff/old.rs",
+            "fixtures/diff/new.rs",
+            removed
+        ))
+        .unwrap(),
+        "```diff\n-fn removed() {}\n```"
+    );
+    assert!(embed_diff_internal_str(quote!(
+        "fixtures/diff/old.rs",
+        "fixtures/diff/new.rs",
+        missing
+    ))
+    .unwrap_err()
+    .to_string()
+    .contains("in either"));
+}
+
+#[test]
+fn test_embed_run_checks_syntax() {
+    let err = embed_internal_str(
+        quote!("fixtures/fragments.rs", greet),
+        MarkdownLanguage::Blank,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("The example embedded from fixtures/fragments.rs:3 would fail"));
+    assert!(embed_internal_str(
+        quote!("fixtures/fragments.rs", greet),
+        MarkdownLanguage::NoRun
+    )
+    .is_err());
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/fragments.rs", greet, no_check),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nfn greet(&self) -> String;\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/fragments.rs", greet),
+        MarkdownLanguage::Ignore
+    )
+    .is_ok());
+    assert!(check_example_syntax("let x = 1;\nprintln!(\"{}\", x);").is_ok());
+}
+
+#[test]
+fn test_embed_docs() {
+    assert_eq!(
+        embed_docs_internal_str(quote!("fixtures/item_docs.rs", connect)).unwrap(),
+        "Connects to the server at the given address.\n\nRetries up to three times before giving up."
+    );
+    assert_eq!(
+        embed_docs_internal_str(quote!("fixtures/item_docs.rs", disconnect)).unwrap(),
+        "Closes the connection.\n  Indented continuation."
+    );
+    let err = embed_docs_internal_str(quote!("fixtures/item_docs.rs", undocumented))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("has no doc comments"));
+    assert!(embed_docs_internal_str(quote!("fixtures/item_docs.rs")).is_err());
+}
+
+#[test]
+fn test_export_stmt() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/export_stmt.rs", create_client),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nlet client = Client::new(\"localhost\");\n// connect before sending anything\n\
+        client.connect();\n```"
+    );
+    assert_eq!(
+        export_stmt_internal(quote!(setup, {
+            let x = 1;
+            x + 1;
+        }))
+        .unwrap()
+        .to_string(),
+        quote!(let x = 1; x + 1;).to_string()
+    );
+    assert!(export_stmt_internal(quote!({
+        let x = 1;
+    }))
+    .is_err());
+}
+
+#[test]
+fn test_embed_hash() {
+    assert_eq!(code_hash(""), "cbf29ce484222325");
+    let hash = code_hash("fn undocumented() {}");
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/item_docs.rs", undocumented, hash = #hash),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn undocumented() {}\n```"
+    );
+    let err = embed_internal_str(
+        quote!("fixtures/item_docs.rs", undocumented, hash = "abc123"),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains(&format!("update the pinned hash to \"{}\"", hash)));
+}
+
+#[test]
+fn test_embed_impl_derived_names() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/impl_names.rs", Foo_impl),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nimpl Foo {\n    fn new() -> Self {\n        Foo\n    }\n}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/impl_names.rs", Foo_Display_impl),
+        MarkdownLanguage::Ignore
+    )
+    .unwrap()
+    .starts_with("```rust,ignore\nimpl std::fmt::Display for Foo {\n"));
+    assert!(embed_internal_str(
+        quote!("fixtures/impl_names.rs", foo_ref_default),
+        MarkdownLanguage::Ignore
+    )
+    .is_ok());
+    assert!(export_internal(quote!(), quote!(impl Foo {})).is_ok());
+    assert!(export_internal(quote!(), quote!(impl Default for &Foo {})).is_err());
+}
+
+#[test]
+fn test_embed_str() {
+    let source = "fn unrelated() {}\n\n#[docify::export]\nfn inline_example() {\n    assert_eq!(1 + 1, 2);\n}\n";
+    assert_eq!(
+        embed_str(source, Some("inline_example"), MarkdownLanguage::Ignore).unwrap(),
+        "```rust,ignore\nfn inline_example() {\n    assert_eq!(1 + 1, 2);\n}\n```"
+    );
+    assert_eq!(
+        embed_str("fn main() {}\n", None, MarkdownLanguage::Blank).unwrap(),
+        "```rust\nfn main() {}\n```"
+    );
+    let err = embed_str(source, Some("missing"), MarkdownLanguage::Ignore)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Could not find docify export item 'missing' in '<inline>'."));
+    assert!(embed_str(source, Some("not an ident"), MarkdownLanguage::Ignore).is_err());
+}
+
+#[test]
+fn test_embed_crlf() {
+    let expected =
+        "```rust,ignore\nfn crlf_example() {\n    let x = 1;\n\n    println!(\"{}\", x);\n}\n```";
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/crlf.rs", crlf_example),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        expected
+    );
+    let whole = embed_internal_str(quote!("fixtures/crlf.rs"), MarkdownLanguage::Ignore).unwrap();
+    assert!(!whole.contains('\r'));
+    assert_eq!(
+        embed_str(
+            &fs::read_to_string("fixtures/crlf.rs").unwrap(),
+            Some("crlf_example"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn test_embed_trims_blank_lines() {
+    let output =
+        embed_internal_str(quote!("fixtures/padded.rs"), MarkdownLanguage::Ignore).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(!lines[1].trim().is_empty());
+    assert!(!lines[lines.len() - 2].trim().is_empty());
+    assert_eq!(
+        output,
+        "```rust,ignore\n// leading and trailing blank lines are trimmed\nfn padded() {\n\n    \
+        let x = 1;\n}\n```"
+    );
+    assert_eq!(into_fenced_example("\n  \n", "rust"), "```rust\n```");
+}
+
+#[test]
+fn test_embed_multiple_targets() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/file.rs", [some_other_fn, some_fn]),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn some_other_fn() {\n    println!(\"bar\");\n}\n```\n\
+        ```rust,ignore\nfn some_fn() {\n    println!(\"foo\");\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/file.rs", [some_fn, 7..=9], single_fence),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn some_fn() {\n    println!(\"foo\");\n}\n\n\
+        fn some_other_fn() {\n    println!(\"bar\");\n}\n```"
+    );
+    assert!(embed_internal_str(quote!("fixtures/file.rs", []), MarkdownLanguage::Ignore).is_err());
+    assert!(embed_internal_str(
+        quote!("fixtures/file.rs", [some_fn, missing]),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_strip_attrs() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/attributes.rs", main, strip_attrs = "tokio::main"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nasync fn main() {\n    run().await;\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/attributes.rs", Config, strip_attrs = "tokio::main, serde"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n#[derive(Debug, serde::Serialize)]\nstruct Config {\n    user_id: u32,\n    \
+        // #[serde(skip)] is mentioned in a comment\n    name: String,\n}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/attributes.rs", main, strip_attrs = "tokio,"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_whole_non_rust_file() {
+    let err = embed_internal_str(quote!("fixtures/notes.txt"), MarkdownLanguage::Ignore)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Cannot embed 'fixtures/notes.txt' as a rust example"));
+    assert!(err.contains("lang = \"text\""));
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/notes.txt", lang = "text"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```text\nThese are some notes, not rust code.\nThey can't be embedded as an example.\n```"
+    );
+}
+
+#[test]
+fn test_embed_category() {
+    assert_eq!(
+        embed_internal_str(
+            quote!(
+                "fixtures/categories.rs",
+                category = "networking",
+                single_fence
+            ),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn connect() {}\n\nfn fetch() {}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/categories.rs", fetch_example),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn fetch() {}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/categories.rs", category = "storage"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn save() {}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/categories.rs", category = "missing"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+    assert!(embed_internal_str(
+        quote!("fixtures/categories.rs", connect, category = "networking"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+    assert!(export_internal(
+        quote!(category = "networking"),
+        quote!(
+            fn f() {}
+        )
+    )
+    .is_ok());
+    assert!(export_internal(
+        quote!(topic = "networking"),
+        quote!(
+            fn f() {}
+        )
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_dir() {
+    let one = "```rust,ignore\nfn step_one() {\n    println!(\"one\");\n}\n```";
+    let two = "```rust,ignore\nfn step_two() {\n    println!(\"two\");\n}\n```";
+    let three = "```rust,ignore\nfn step_three() {\n    println!(\"three\");\n}\n```";
+    assert_eq!(
+        embed_dir_internal_str(quote!("fixtures/tutorial/")).unwrap(),
+        [one, two].join("\n")
+    );
+    assert_eq!(
+        embed_dir_internal_str(quote!("fixtures/tutorial", recursive)).unwrap(),
+        [three, one, two].join("\n")
+    );
+    assert!(embed_dir_internal_str(quote!("fixtures/subfolder")).is_err());
+    assert!(embed_dir_internal_str(quote!("fixtures/tutorial", recursive = "yes")).is_err());
+}
+
+#[test]
+fn test_embed_placeholder() {
+    assert_eq!(
+        embed_internal_str(
+            quote!(
+                "fixtures/hidden.rs",
+                long_example,
+                placeholder = "/* omitted for brevity */"
+            ),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nfn long_example() {\n    let client = 1;\n    /* omitted for brevity */\n    \
+        println!(\"{}\", client);\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!(
+                "fixtures/variants.rs",
+                Message::Close,
+                placeholder = "/* snip */"
+            ),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nenum Message {\n    /* snip */\n    Close(u16),\n    /* snip */\n}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!(
+            "fixtures/hidden.rs",
+            long_example,
+            placeholder = "/* a\nb */"
+        ),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+    assert!(embed_internal_str(
+        quote!("fixtures/hidden.rs", long_example, placeholder = " "),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_parse_error_location() {
+    let err = embed_internal_str(
+        quote!("fixtures/invalid.rs", broken),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("at line 11, column 17"), "{err}");
+    assert!(err.contains("invalid.rs"));
+}
+
+#[test]
+fn test_parse_error_position() {
+    let located = |source: &str| {
+        parse_source_file(source, Path::new("f.rs"))
+            .map(|_| ())
+            .unwrap_err()
+            .to_string()
+    };
+    assert!(located(
+        "fn f() {}
+fn g() { let x = 1 + ; }"
+    )
+    .contains("at line 2, column 22"));
+    assert!(located(
+        "/// é
+fn f() { x y }"
+    )
+    .contains("at line 2, column 12"));
+    assert!(located("fn f() { let x = 1 + }").contains("at line 1, column 22"));
+    assert!(located(
+        "fn f() {
+    (]
+}"
+    )
+    .contains("at line 2, column 6"));
+    assert!(located("fn f() { \"unclosed }").contains("at line 1, column 10"));
+    assert!(located("fn f() {}\n/* unclosed").contains("at line 2, column 1"));
+    assert!(located("fn f()").contains("at line 1, column 6"));
+    assert!(located("struct S").contains("at the end of 'f.rs'"));
+}
+
+#[test]
+fn test_embed_item_lines() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/big_fn.rs", big_fn, lines = 6..=8, show_source),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nif total > 40 {\n    println!(\"big total: {}\", total);\n}\n\
+        // from fixtures/big_fn.rs:7\n```"
+    );
+    let err = embed_internal_str(
+        quote!("fixtures/big_fn.rs", big_fn, lines = 5..20),
+        MarkdownLanguage::Blank,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("the item 'big_fn' only has 10 lines"), "{err}");
+    assert!(embed_internal_str(
+        quote!("fixtures/big_fn.rs", lines = 1..3),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_duplicates_in_source_order() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/ordering.rs", Ordered),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\n#[derive(Debug)]\n#[allow(dead_code)]\n#[derive(Clone)]\nstruct First;\n```\n\
+        ```rust\npub fn second() {\n    fn third() {}\n    third();\n}\n```\n\
+        ```rust\nfn third() {}\n```\n\
+        ```rust\n#[cfg_attr(test, allow(unused))]\n/// The fourth item.\npub struct Fourth;\n```\n\
+        ```rust\nconst FIFTH: u8 = 5;\n```\n\
+        ```rust\n#[inline]\nfn sixth() {}\n```"
+    );
+    // the export attribute that matches is found by its position among all of the attributes
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/ordering.rs", Other),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\n#[inline]\nfn sixth() {}\n```"
+    );
+}
+
+#[test]
+fn test_embed_raw() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/duplicates.rs", Duplicate, raw),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "struct StructOne;\n\nstruct StructTwo;"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/duplicates.rs", Single, raw),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+    assert!(embed_internal_str(
+        quote!("fixtures/duplicates.rs", Single, raw, title = "single.rs"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_generics_preserved() {
+    let source = std::fs::read_to_string("fixtures/generics.rs").unwrap();
+    let item = source.trim_start_matches("#[docify::export]\n").trim_end();
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/generics.rs", merge_all),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        format!("```rust\n{}\n```", item)
+    );
+    let signature = item[..item.find("\n{").unwrap()].trim_end_matches(',');
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/generics.rs", merge_all, signature_only),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        format!("```rust,ignore\n{};\n```", signature)
+    );
+}
+
+#[test]
+fn test_embed_raw_idents() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/raw_idents.rs", r#type),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\npub struct r#type {\n    pub r#match: bool,\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/raw_idents.rs", r#loop),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn run_forever() {}\n```"
+    );
+    // raw idents refer to the same name as their plain counterparts
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/raw_idents.rs", r#plain),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn plain() {}\n```"
+    );
+}
+
+#[test]
+fn test_embed_keyword_ident() {
+    let err = embed_internal_str(
+        quote!("fixtures/raw_idents.rs", type),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("`type` is a keyword"));
+    assert!(err.contains("`r#type`"));
+}
+
+#[test]
+fn test_embed_multiple_source_paths() {
+    assert_eq!(
+        embed_internal_str(
+            quote!(
+                [
+                    "fixtures/split/shape.rs",
+                    "fixtures/split/unrelated.rs",
+                    "fixtures/split/square.rs"
+                ],
+                Shape
+            ),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\npub trait Shape {\n    fn area(&self) -> f64;\n}\n```\n\
+        ```rust,ignore\nimpl Shape for Square {\n    fn area(&self) -> f64 {\n        \
+        self.0 * self.0\n    }\n}\n```"
+    );
+    let err = embed_internal_str(
+        quote!(
+            ["fixtures/split/shape.rs", "fixtures/split/unrelated.rs"],
+            Missing
+        ),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert_eq!(
+        err,
+        "Could not find docify export item 'Missing' in any of 'fixtures/split/shape.rs', \
+        'fixtures/split/unrelated.rs'."
+    );
+}
+
+#[test]
+fn test_embed_emit_metadata() {
+    // the metadata isn't part of the embedded code
+    assert!(embed_internal_str(
+        quote!("fixtures/big_fn.rs", big_fn, hash = "d006bf81aa481087"),
+        MarkdownLanguage::Ignore
+    )
+    .is_ok());
+    assert!(embed_internal_str(
+        quote!(
+            "fixtures/big_fn.rs",
+            big_fn,
+            emit_metadata,
+            hash = "d006bf81aa481087"
+        ),
+        MarkdownLanguage::Ignore
+    )
+    .is_ok());
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/duplicates.rs", Duplicate, emit_metadata),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "<!-- docify: {\"path\":\"fixtures/duplicates.rs\",\"item\":\"Duplicate\",\"lines\":[2,2]} -->\n\
+        ```rust,ignore\nstruct StructOne;\n```\n\
+        <!-- docify: {\"path\":\"fixtures/duplicates.rs\",\"item\":\"Duplicate\",\"lines\":[6,6]} -->\n\
+        ```rust,ignore\nstruct StructTwo;\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/notes.txt", lang = "text", emit_metadata),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "<!-- docify: {\"path\":\"fixtures/notes.txt\",\"item\":null,\"lines\":[1,2]} -->\n\
+        ```text\nThese are some notes, not rust code.\nThey can't be embedded as an example.\n```"
+    );
+}
+
+#[test]
+fn test_embed_value_only() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/values.rs", CONFIG, value_only),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nConfig {\n    name: \"docify\",\n    retries: 3, // per request\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/values.rs", [LIMIT, ports], value_only),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n10 * 3\n```\n```rust,ignore\n[8080, 8081, 8082]\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/consts.rs", not_a_const, value_only),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_numbered() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/duplicates.rs", [Duplicate, Single], numbered),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "**Step 1**\n\n```rust,ignore\nstruct StructOne;\n```\n\
+        **Step 2**\n\n```rust,ignore\nstruct StructTwo;\n```\n\
+        **Step 3**\n\n```rust,ignore\nstruct Single;\n```"
+    );
+}
+
+#[test]
+fn test_embed_cfg_attr_export() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/cfg_attr.rs", documented),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\npub fn enabled_in_docs() {}\n```\n\
+        ```rust,ignore\n#[derive(Debug)]\npub struct DisabledInDocs;\n```"
+    );
+    std::env::set_var("CARGO_FEATURE_DOCIFY_TEST_ENABLED", "1");
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/cfg_attr.rs", documented, respect_cfg),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\npub fn enabled_in_docs() {}\n```"
+    );
+}
+
+#[test]
+fn test_embed_contents_only() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/module_contents.rs", example, contents_only),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nuse std::collections::HashMap;\n\n\
+        pub fn build() -> HashMap<&'static str, u32> {\n    let mut map = HashMap::new();\n    \
+        map.insert(\"answer\", 42);\n    map\n}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/module_contents.rs", NoContents, contents_only),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_swapped_args() {
+    let err = embed_internal_str(
+        quote!(Single, "fixtures/duplicates.rs"),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert_eq!(
+        err,
+        "The first argument must be a string literal path, did you swap the path and item \
+        name? i.e. `docify::embed!(\"fixtures/duplicates.rs\", Single)`"
+    );
+    let err = embed_internal_str(quote!(Single), MarkdownLanguage::Ignore)
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        err,
+        "The first argument must be a string literal path to the source file."
+    );
+}
+
+#[test]
+fn test_embed_wrap_main() {
+    assert_eq!(
+        embed_internal_str(
+            quote!(
+                "fixtures/statements.rs",
+                [create_client, connect],
+                wrap_main,
+                single_fence
+            ),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nfn main() {\n    let client = Client::new(\"localhost\");\n\n    \
+        client.connect(&config);\n}\n```"
+    );
+    // complete items are left as-is
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/duplicates.rs", Single, wrap_main),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust\nstruct Single;\n```"
+    );
+}
+
+#[test]
+fn test_embed_sort() {
+    let embed = |sort: &str| {
+        embed_internal_str(
+            quote!("fixtures/ordering.rs", Ordered, sort = #sort),
+            MarkdownLanguage::Ignore,
+        )
+        .unwrap()
+        .lines()
+        .filter(|line| {
+            line.starts_with("struct")
+                || line.starts_with("pub")
+                || line.starts_with("const")
+                || line.starts_with("fn")
+        })
+        .map(String::from)
+        .collect::<Vec<String>>()
+    };
+    assert_eq!(
+        embed("name"),
+        [
+            "const FIFTH: u8 = 5;",
+            "struct First;",
+            "pub struct Fourth;",
+            "pub fn second() {",
+            "fn sixth() {}",
+            "fn third() {}",
+        ]
+    );
+    assert_eq!(
+        embed("reverse"),
+        [
+            "fn sixth() {}",
+            "const FIFTH: u8 = 5;",
+            "pub struct Fourth;",
+            "fn third() {}",
+            "pub fn second() {",
+            "struct First;",
+        ]
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/ordering.rs", Ordered, sort = "size"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_show_desc() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/descriptions.rs", setup, show_desc, emit_metadata),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "Sets up the \\*client\\*, see \\`connect\\`\n\
+        <!-- docify: {\"path\":\"fixtures/descriptions.rs\",\"item\":\"setup\",\"lines\":[1,3]} -->\n\
+        ```rust,ignore\n#[derive(Default)]\npub struct Client;\n```\n\
+        <!-- docify: {\"path\":\"fixtures/descriptions.rs\",\"item\":\"setup\",\"lines\":[6,6]} -->\n\
+        ```rust,ignore\npub fn connect(client: &Client) {}\n```"
+    );
+    // descriptions are only shown when asked for
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/descriptions.rs", setup),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n#[derive(Default)]\npub struct Client;\n```\n\
+        ```rust,ignore\npub fn connect(client: &Client) {}\n```"
+    );
+}
+
+#[test]
+fn test_embed_from_marker() {
+    assert_eq!(
+        embed_internal_str(
+            quote!(
+                "fixtures/with_header.rs",
+                from_marker = "// docify:start",
+                show_source
+            ),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn main() {\n    let mut map = HashMap::new();\n    \
+        map.insert(1, \"one\");\n}\n// from fixtures/with_header.rs:7\n```"
+    );
+    let err = embed_internal_str(
+        quote!("fixtures/with_header.rs", from_marker = "// docify:begin"),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("`from_marker` sentinel `// docify:begin`"));
+}
+
+#[test]
+fn test_embed_backticks_in_source() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/backticks.rs", README),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "````rust,ignore\nconst README: &str = \"\n```rust\nlet x = 1;\n```\n\";\n````"
+    );
+}
+
+#[test]
+fn test_list_exports() {
+    assert_eq!(
+        list_exports_internal(quote!("fixtures/aliases.rs"))
+            .unwrap()
+            .to_string(),
+        quote!(["setup", "setup_example", "setup"]).to_string()
+    );
+    let names = file_exports(Path::new("fixtures/export_stmt.rs")).unwrap();
+    assert_eq!(names, vec!["create_client"]);
+    assert!(list_exports_internal(quote!("fixtures/malformed.rs"))
+        .unwrap_err()
+        .to_string()
+        .contains("malformed docify export attribute"));
+}
+
+#[test]
+fn test_assert_all_embedded() {
+    let err =
+        assert_all_embedded_internal(quote!("fixtures/orphans/examples", "fixtures/orphans/docs"))
+            .unwrap_err()
+            .to_string();
+    assert_eq!(
+        err,
+        "Found 1 docify export(s) that are never embedded in 'fixtures/orphans/docs': \
+        'forgotten' in 'fixtures/orphans/examples/basics.rs'."
+    );
+    assert!(assert_all_embedded_internal(quote!(
+        "fixtures/orphans/examples/files",
+        "fixtures/orphans/docs"
+    ))
+    .unwrap()
+    .is_empty());
+    assert!(assert_all_embedded_internal(quote!(
+        "fixtures/orphans/missing",
+        "fixtures/orphans/docs"
+    ))
+    .unwrap_err()
+    .to_string()
+    .contains("Could not find the directory"));
+}
+
+#[test]
+fn test_embed_indent() {
+    assert_eq!(
+        embed_internal_str(
+            quote!(
+                "fixtures/group.rs",
+                counter_example,
+                indent = 4,
+                single_fence
+            ),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "    ```rust,ignore\n    struct Counter {\n        count: u32,\n    }\n    \n    \
+        impl Counter {\n        fn increment(&mut self) {\n            self.count += 1;\n        \
+        }\n    }\n    \n    fn new_counter() -> Counter {\n        Counter { count: 0 }\n    \
+        }\n    ```"
+    );
+    let err = embed_internal_str(
+        quote!("fixtures/group.rs", counter_example, indent = 64),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("must be less than 64"));
+}
+
+#[test]
+fn test_embed_doc_hidden() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/doc_hidden.rs", internal_helper),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\npub fn internal_helper() -> u32 {\n    42\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/doc_hidden.rs", Handle),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\npub struct Handle {\n    pub id: u32,\n    pub generation: u32,\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/doc_hidden.rs", internal_helper, keep_doc_hidden),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n#[doc(hidden)]\npub fn internal_helper() -> u32 {\n    42\n}\n```"
+    );
+}
+
+#[test]
+fn test_embed_no_attrs() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/attributes.rs", Config, no_attrs),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nstruct Config {\n    user_id: u32,\n    \
+        // #[serde(skip)] is mentioned in a comment\n    name: String,\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!(
+                "fixtures/doc_hidden.rs",
+                internal_helper,
+                no_attrs,
+                keep_doc_hidden
+            ),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n#[doc(hidden)]\npub fn internal_helper() -> u32 {\n    42\n}\n```"
+    );
+}
+
+#[test]
+fn test_embed_strip_test() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/tests.rs", example_sync, strip_test),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn example_sync() {\n    let items: Vec<u32> = Vec::new();\n    \
+        items.first().expect(\"empty\");\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/tests.rs", example_async, strip_test),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n#[cfg_attr(miri, ignore)]\nasync fn example_async() {\n    \
+        let value = fetch().await;\n    assert_eq!(value, 1);\n}\n```"
+    );
+}
+
+#[test]
+fn test_embed_trims_trailing_whitespace() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/trailing.rs", padded),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn padded() {\n    let banner = \"first   \n    second\";\n\t\
+        println!(\"{}\", banner);\n}\n```"
+    );
+}
+
+#[test]
+fn test_embed_regex() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/handlers.rs", regex = "^handle_"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn handle_get() {}\n```\n```rust,ignore\nfn post() {}\n```\n\
+        ```rust,ignore\nput();\n```"
+    );
+    let err = embed_internal_str(
+        quote!("fixtures/handlers.rs", regex = "^delete_"),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("Could not find any docify export items with a name matching regex"));
+    let err = embed_internal_str(
+        quote!("fixtures/handlers.rs", regex = "handle_("),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("Invalid `regex` option"));
+    assert!(embed_internal_str(
+        quote!("fixtures/handlers.rs", route, regex = "^handle_"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_export_site_warnings() {
+    let output = export_internal(
+        quote!(ffi),
+        quote!(
+            extern "C" {
+                fn abs(x: i32) -> i32;
+            }
+        ),
+    )
+    .unwrap()
+    .to_string();
+    assert!(output.contains("deprecated"));
+    assert!(output.contains("`extern` blocks have no name of their own"));
+    let output = export_internal(
+        quote!(unfinished),
+        quote!(
+            const X: u8;
+        ),
+    )
+    .unwrap()
+    .to_string();
+    assert!(output.contains("uses syntax that docify can't parse"));
+    let output = export_internal(
+        quote!(),
+        quote!(
+            fn plain() {}
+        ),
+    )
+    .unwrap()
+    .to_string();
+    assert!(!output.contains("deprecated"));
+}
+
+#[test]
+fn test_embed_inner_fns() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/inner_fns.rs", inner),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn helper(x: u32) -> u32 {\n    x * 2\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/inner_fns.rs", deeply_nested),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nfn deeply_nested() {}\n```"
+    );
+}
+
+#[test]
+fn test_embed_edition() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/duplicates.rs", Single, edition = "2018"),
+            MarkdownLanguage::Blank
+        )
+        .unwrap(),
+        "```rust,edition2018\nstruct Single;\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!(
+                "fixtures/duplicates.rs",
+                Single,
+                edition = "2021",
+                fence = "ignore"
+            ),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```ignore,edition2021\nstruct Single;\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/duplicates.rs", Single, edition = "2020"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+    assert!(embed_internal_str(
+        quote!("fixtures/config.toml", edition = "2021", lang = "toml"),
+        MarkdownLanguage::Ignore
+    )
+    .unwrap_err()
+    .to_string()
+    .contains("cannot be combined with the `lang` option"));
+}
+
+#[test]
+fn test_embed_file_without_exports() {
+    let err = embed_str(
+        "fn forgot_to_export() {}\n",
+        Some("forgot_to_export"),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains(
+        "The file '<inline>' contains no #[docify::export] items at all, so make sure the item is \
+        annotated with #[docify::export] (or #[docify::export(forgot_to_export)])."
+    ));
+    let err = embed_internal_str(
+        quote!("fixtures/group.rs", something_else),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(!err.contains("contains no #[docify::export] items"));
+}
+
+#[test]
+fn test_embed_pub_only() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/visibility.rs", api, pub_only),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nmod api {\n    /// Creates a new registry.\n    \
+        pub fn new_registry() -> Registry {\n        Registry::default()\n    }\n\n    \
+        #[derive(Default)]\n    pub struct Registry {\n        entries: HashMap<String, u32>,\n    \
+        }\n\n    impl Registry {\n        pub(crate) fn len(&self) -> usize {\n            \
+        self.entries.len()\n        }\n    }\n\n    pub mod errors {\n        \
+        pub struct NotFound;\n    }\n}\n```"
+    );
+    assert!(embed_internal_str(
+        quote!("fixtures/visibility.rs", api, pub_only, contents_only),
+        MarkdownLanguage::Ignore
+    )
+    .unwrap()
+    .starts_with("```rust,ignore\n/// Creates a new registry.\npub fn new_registry()"));
+    assert!(embed_internal_str(
+        quote!("fixtures/group.rs", counter_example, pub_only),
+        MarkdownLanguage::Ignore
+    )
+    .unwrap_err()
+    .to_string()
+    .contains("can only be used with inline modules"));
+}
+
+#[test]
+fn test_embed_keeps_attribute_order() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/attr_order.rs", Level),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n#[derive(Debug)]\n#[repr(u8)]\nenum Level {\n    Low = 1,\n    \
+        High = 2,\n}\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/attr_order.rs", doubled),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\n#[inline]\n#[must_use]\n#[allow(clippy::identity_op)]\n\
+        #[cfg_attr(test, allow(dead_code))]\nfn double(x: u32) -> u32 {\n    x * 2\n}\n```"
+    );
+    // the found item itself, rather than just its excerpt, keeps the other attributes in order
+    let file = syn::parse_file(&fs::read_to_string("fixtures/attr_order.rs").unwrap()).unwrap();
+    let mut visitor = ItemVisitor::new(
+        ItemSearch::Name(Ident::new("Level", Span::call_site())),
+        None,
+    );
+    visitor.visit_file(&file);
+    let item = parse2::<Item>(visitor.results[0].0.clone()).unwrap();
+    assert_eq!(
+        item.item_attributes()
+            .iter()
+            .map(|attr| attr.to_token_stream().to_string())
+            .collect::<Vec<String>>(),
+        vec!["# [derive (Debug)]", "# [repr (u8)]"]
+    );
+}
+
+#[test]
+fn test_embed_region() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/regions.rs", region = "arms"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nKind::Get => fetch(request),\nKind::Post => create(request),\n\
+        Kind::Put => update(request),\n```"
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/regions.rs", region = "writes", show_source),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nKind::Post => create(request),\nKind::Put => update(request),\n\
+        // from fixtures/regions.rs:6\n```\n```rust,ignore\n0x01, 0x02,\n\
+        // from fixtures/regions.rs:17\n```"
+    );
+    let err = embed_internal_str(
+        quote!(
+            "fixtures/regions_unclosed.txt",
+            region = "open",
+            lang = "text"
+        ),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains(
+        "Found `docify:region-start open` without a matching `docify:region-end` on line 1"
+    ));
+    let err = embed_internal_str(
+        quote!(
+            "fixtures/regions_overlapping.txt",
+            region = "same",
+            lang = "text"
+        ),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("regions with the same name cannot overlap"));
+    let err = embed_internal_str(
+        quote!("fixtures/regions.rs", region = "missing"),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("Could not find the region `docify:region-start missing`"));
+}
+
+#[test]
+fn test_embed_foreign_items() {
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/ffi.rs", c_abs), MarkdownLanguage::Ignore).unwrap(),
+        "```rust,ignore\nfn abs(x: i32) -> i32;\n```"
+    );
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/ffi.rs", errno), MarkdownLanguage::Ignore).unwrap(),
+        "```rust,ignore\nstatic errno: i32;\n```"
+    );
+    assert_eq!(
+        embed_internal_str(quote!("fixtures/ffi.rs", foo), MarkdownLanguage::Ignore).unwrap(),
+        "```rust,ignore\nfn foo();\n```"
+    );
+    let output = export_internal(
+        quote!(),
+        quote!(
+            fn foo();
+        ),
+    )
+    .unwrap()
+    .to_string();
+    assert!(!output.contains("deprecated"));
+}
+
+#[test]
+fn test_try_embed() {
+    assert_eq!(
+        try_embed_internal_str(quote!("fixtures/ffi.rs", foo)).unwrap(),
+        embed_internal_str(quote!("fixtures/ffi.rs", foo), MarkdownLanguage::Ignore).unwrap()
+    );
+    assert_eq!(
+        try_embed_internal_str(quote!("fixtures/ffi.rs", missing)).unwrap(),
+        "```rust,ignore\n// [docify: could not embed 'missing' from 'fixtures/ffi.rs']\n```"
+    );
+    assert_eq!(
+        try_embed_internal_str(quote!("fixtures/missing.rs")).unwrap(),
+        "```rust,ignore\n// [docify: could not embed 'fixtures/missing.rs']\n```"
+    );
+    assert!(try_embed_internal_str(quote!(foo)).is_err());
+}
+
+#[test]
+fn test_embed_highlight() {
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/generics.rs", merge_all, highlight = "merged"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap()
+        .lines()
+        .filter(|line| line.contains("merged") || line.starts_with('<'))
+        .collect::<Vec<&str>>(),
+        vec![
+            "<pre><code class=\"language-rust\">pub fn merge_all&lt;'a, 'b: 'a, K, V, I, const N: usize&gt;(",
+            "<mark>    let mut merged = maps.into_iter().flatten().collect::&lt;std::collections::BTreeMap&lt;_, _&gt;&gt;();</mark>",
+            "<mark>    merged.extend(extra.iter().map(|(k, v)| (k, v)));</mark>",
+            "<mark>    merged</mark>",
+            "</code></pre>",
+        ]
+    );
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/notes.txt", highlight = "embedded", lang = "text"),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "<pre><code class=\"language-text\">These are some notes, not rust code.\n\
+        <mark>They can't be embedded as an example.</mark>\n</code></pre>"
+    );
+    let err = embed_internal_str(
+        quote!("fixtures/file.rs", some_fn, highlight = "print"),
+        MarkdownLanguage::Ignore,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("No line of the embedded code contains `print`"));
+    assert!(embed_internal_str(
+        quote!("fixtures/file.rs", some_fn, highlight = "two words"),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+    assert!(embed_internal_str(
+        quote!("fixtures/file.rs", some_fn, highlight = "some_fn"),
+        MarkdownLanguage::Blank
+    )
+    .is_err());
+    assert!(embed_internal_str(
+        quote!(
+            "fixtures/file.rs",
+            some_fn,
+            highlight = "some_fn",
+            indent = 2
+        ),
+        MarkdownLanguage::Ignore
+    )
+    .is_err());
+}
+
+#[test]
+fn test_embed_struct_forms() {
+    let cases = [
+        (quote!(Unit), "struct Unit;"),
+        (quote!(Tuple), "struct Tuple(u32, pub String);"),
+        (quote!(Wrapper), "struct Wrapper<T>(T)\nwhere\n    T: Copy;"),
+        (quote!(Named), "struct Named {\n    field: u32,\n}"),
+        (quote!(Spaced), "struct Spaced ;"),
+    ];
+    for (item, expected) in cases {
+        assert_eq!(
+            embed_internal_str(
+                quote!("fixtures/structs.rs", #item),
+                MarkdownLanguage::Ignore
+            )
+            .unwrap(),
+            format!("```rust,ignore\n{}\n```", expected)
+        );
+    }
+    assert_eq!(
+        embed_internal_str(
+            quote!("fixtures/structs.rs", [Unit, Tuple], single_fence),
+            MarkdownLanguage::Ignore
+        )
+        .unwrap(),
+        "```rust,ignore\nstruct Unit;\n\nstruct Tuple(u32, pub String);\n```"
+    );
+    export_internal(
+        quote!(),
+        quote!(
+            struct Tuple(u32);
+        ),
+    )
+    .unwrap();
+    export_internal(
+        quote!(),
+        quote!(
+            struct Unit;
+        ),
+    )
+    .unwrap();
 }
@@ -42,7 +42,7 @@
 //! ```
 //! /// These are some docs about an item. You can embed examples, tests, and
 //! /// other items directly into docs using the following macro:
-//! /// ```ignore
+//! /// ```rust,ignore
 //! /// fn some_example() {
 //! ///     assert_eq!(2 + 2, 4);
 //! ///     assert_eq!(2 + 3, 5);
@@ -61,7 +61,8 @@
 //! Note that you can also use the [`embed_run!`](`macro@embed_run`) version of the macro to
 //! make the embedded example compile/run as part of doc tests, which is desirable in certain
 //! situations even though typically the example will already be running/compiling somewhere
-//! else in your project.
+//! else in your project. If the example should be compiled but not run, such as one that
+//! accesses the network, use [`embed_no_run!`](`macro@embed_no_run`) instead.
 //!
 //! ## Dynamic Embedding in Markdown Files
 //!